@@ -0,0 +1,267 @@
+//! This crate provides procedural macros for the [aector](https://docs.rs/aector) crate. These are
+//! re-exported by aector if its `macros` feature is enabled, which is the default.
+//!
+//! The [macro@actor] attribute is used to generate a `Behavior` from an `impl` block. All methods
+//! annotated with `#[tell]` or `#[ask]` are registered as message handlers for the type of their
+//! message argument and all methods annotated with one of `#[on_start]`, `#[on_kill]`, `#[on_error]`,
+//! `#[on_restart]` or `#[on_stop]` are registered as the respective action.
+//!
+//! Example:
+//! ```ignore
+//! use aector::actor::{ActorContext, MailboxType};
+//! use aector::behavior::{Behavior, BehaviorAction};
+//! use aector::Addr;
+//!
+//! struct Counter { count: i32 }
+//!
+//! #[aector::actor]
+//! impl Counter {
+//!     #[on_start]
+//!     fn start(&mut self, _ctx: &mut ActorContext) {
+//!         self.count = 0;
+//!     }
+//!
+//!     #[tell]
+//!     fn add(&mut self, msg: i32, _ctx: &mut ActorContext) -> BehaviorAction<Counter> {
+//!         self.count += msg;
+//!         Behavior::keep()
+//!     }
+//!
+//!     #[ask]
+//!     fn get(&mut self, _msg: (), reply_to: Addr, _ctx: &mut ActorContext) -> BehaviorAction<Counter> {
+//!         reply_to.tell(self.count);
+//!         Behavior::keep()
+//!     }
+//! }
+//!
+//! // Counter::behavior() returns the generated behavior, into_actor also creates the actor
+//! let actor = Counter { count: 0 }.into_actor(MailboxType::Unbounded);
+//! ```
+
+use std::collections::HashSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, Error, FnArg, ImplItem, ImplItemFn, ItemImpl, Meta, Type};
+
+/// Kinds of methods which can be annotated inside of an `#[actor]` impl block.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HandlerKind {
+    Tell,
+    Ask,
+    Action(&'static str)
+}
+
+impl HandlerKind {
+    fn from_attr(attr: &Attribute) -> Option<Self> {
+        let ident = attr.path().get_ident()?.to_string();
+        match ident.as_str() {
+            "tell" => Some(HandlerKind::Tell),
+            "ask" => Some(HandlerKind::Ask),
+            "on_start" => Some(HandlerKind::Action("on_start")),
+            "on_kill" => Some(HandlerKind::Action("on_kill")),
+            "on_error" => Some(HandlerKind::Action("on_error")),
+            "on_restart" => Some(HandlerKind::Action("on_restart")),
+            "on_stop" => Some(HandlerKind::Action("on_stop")),
+            _ => None
+        }
+    }
+
+    /// Number of arguments (excluding self) which annotated methods of this kind have to take.
+    fn arg_count(&self) -> usize {
+        match self {
+            HandlerKind::Tell => 2,
+            HandlerKind::Ask => 3,
+            HandlerKind::Action(_) => 1
+        }
+    }
+}
+
+/// Generates a `Behavior` and an `Actor` constructor from an `impl` block. See the
+/// [crate level documentation](crate) for an example.
+///
+/// The following associated functions are added to the type:
+/// - `behavior() -> Behavior<Self>` returns a new behavior with all annotated handlers and actions.
+/// - `into_actor(self, mailbox_type: MailboxType) -> Actor<Self>` creates an actor with self as
+///   its initial state and the generated behavior.
+///
+/// Defining more than one handler of the same kind (tell / ask) for the same message type or more
+/// than one method for the same action results in a compile error. Message types are compared as
+/// written, thus duplicates which are written differently, e.g. `Foo` and `crate::Foo` or a type
+/// alias, are only detected once `behavior()` is called, which panics naming both methods.
+#[proc_macro_attribute]
+pub fn actor(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return Error::new(Span::call_site(), "#[actor] does not take any arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let item_impl = parse_macro_input!(item as ItemImpl);
+    match expand_actor(item_impl) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into()
+    }
+}
+
+fn expand_actor(mut item_impl: ItemImpl) -> Result<TokenStream2, Error> {
+    if let Some((_, path, _)) = &item_impl.trait_ {
+        return Err(Error::new(path.span(), "#[actor] can only be used on inherent impl blocks"));
+    }
+
+    let self_ty = item_impl.self_ty.clone();
+    let mut registrations = Vec::new();
+    let mut handlers = Vec::new();
+    let mut tell_types = HashSet::new();
+    let mut ask_types = HashSet::new();
+    let mut actions = HashSet::new();
+
+    for impl_item in item_impl.items.iter_mut() {
+        let method = match impl_item {
+            ImplItem::Fn(method) => method,
+            _ => continue
+        };
+
+        let kind = match take_handler_attr(method)? {
+            Some(kind) => kind,
+            None => continue
+        };
+
+        check_signature(method, kind)?;
+        let method_name = &method.sig.ident;
+
+        match kind {
+            HandlerKind::Tell | HandlerKind::Ask => {
+                let msg_type = message_type(method)?;
+                // types are compared by their tokens, which is sufficient to catch duplicates written the same way,
+                // other duplicates are detected by comparing type ids when the behavior is generated
+                let type_key = msg_type.to_token_stream().to_string();
+                let (seen, kind_name) = match kind {
+                    HandlerKind::Tell => (&mut tell_types, "tell"),
+                    _ => (&mut ask_types, "ask")
+                };
+                if !seen.insert(type_key) {
+                    return Err(Error::new(
+                        msg_type.span(),
+                        format!("duplicate {} handler for message type `{}`", kind_name, msg_type.to_token_stream())
+                    ));
+                }
+
+                let method_str = method_name.to_string();
+                handlers.push(quote! {
+                    (::std::any::TypeId::of::<#msg_type>(), ::std::any::type_name::<#msg_type>(), #kind_name, #method_str)
+                });
+
+                let registration = if kind == HandlerKind::Tell {
+                    quote_spanned! {method.sig.span()=>
+                        .on_tell::<#msg_type>(|msg, state, ctx| -> ::aector::behavior::BehaviorAction<#self_ty> {
+                            <#self_ty>::#method_name(state, msg, ctx)
+                        })
+                    }
+                } else {
+                    quote_spanned! {method.sig.span()=>
+                        .on_ask::<#msg_type>(|msg, state, reply_to, ctx| -> ::aector::behavior::BehaviorAction<#self_ty> {
+                            <#self_ty>::#method_name(state, msg, reply_to, ctx)
+                        })
+                    }
+                };
+                registrations.push(registration);
+            }
+            HandlerKind::Action(action) => {
+                if !actions.insert(action) {
+                    return Err(Error::new(
+                        method.sig.ident.span(),
+                        format!("duplicate {} action, only one method can be annotated with #[{}]", action, action)
+                    ));
+                }
+
+                let builder_fn = syn::Ident::new(action, method.sig.ident.span());
+                registrations.push(quote_spanned! {method.sig.span()=>
+                    .#builder_fn(|state: &mut #self_ty, ctx: &mut ::aector::actor::ActorContext| {
+                        <#self_ty>::#method_name(state, ctx)
+                    })
+                });
+            }
+        }
+    }
+
+    let (impl_generics, _, where_clause) = item_impl.generics.split_for_impl();
+
+    Ok(quote! {
+        #item_impl
+
+        impl #impl_generics #self_ty #where_clause {
+            /// Returns the behavior generated from the annotated methods of this type.
+            pub fn behavior() -> ::aector::behavior::Behavior<#self_ty> {
+                let handlers: &[(::std::any::TypeId, &str, &str, &str)] = &[#(#handlers),*];
+                for (i, (type_id, type_name, kind, method)) in handlers.iter().enumerate() {
+                    if let Some((.., other)) = handlers[..i].iter().find(|(other_id, _, other_kind, _)| other_id == type_id && other_kind == kind) {
+                        panic!("#[actor] methods `{}` and `{}` both define a {} handler for message type {}", other, method, kind, type_name);
+                    }
+                }
+
+                ::aector::behavior::BehaviorBuilder::<#self_ty>::new()
+                    #(#registrations)*
+                    .build()
+            }
+
+            /// Creates an actor with self as its initial state and the generated behavior.
+            pub fn into_actor(self, mailbox_type: ::aector::actor::MailboxType) -> ::aector::actor::Actor<#self_ty> {
+                ::aector::actor::Actor::new(self, Self::behavior(), mailbox_type)
+            }
+        }
+    })
+}
+
+/// Removes the handler attribute from the given method and returns its kind.
+fn take_handler_attr(method: &mut ImplItemFn) -> Result<Option<HandlerKind>, Error> {
+    let mut kind = None;
+    let mut result = Ok(());
+
+    method.attrs.retain(|attr| {
+        match HandlerKind::from_attr(attr) {
+            None => true,
+            Some(attr_kind) => {
+                if !matches!(attr.meta, Meta::Path(_)) {
+                    result = Err(Error::new(attr.span(), "handler attributes do not take any arguments"));
+                } else if kind.is_some() {
+                    result = Err(Error::new(attr.span(), "a method can only be annotated with one handler attribute"));
+                }
+                kind = Some(attr_kind);
+                false
+            }
+        }
+    });
+
+    result.map(|_| kind)
+}
+
+/// Checks that the method takes &mut self and the number of arguments required by its kind.
+fn check_signature(method: &ImplItemFn, kind: HandlerKind) -> Result<(), Error> {
+    let sig = &method.sig;
+    let receiver_ok = match sig.inputs.first() {
+        Some(FnArg::Receiver(receiver)) => receiver.reference.is_some() && receiver.mutability.is_some(),
+        _ => false
+    };
+
+    let expected = match kind {
+        HandlerKind::Tell => "(&mut self, msg: M, ctx: &mut ActorContext) -> BehaviorAction<Self>",
+        HandlerKind::Ask => "(&mut self, msg: M, reply_to: Addr, ctx: &mut ActorContext) -> BehaviorAction<Self>",
+        HandlerKind::Action(_) => "(&mut self, ctx: &mut ActorContext)"
+    };
+
+    if !receiver_ok || sig.inputs.len() != kind.arg_count() + 1 {
+        return Err(Error::new(sig.span(), format!("expected a method with the signature {}", expected)));
+    }
+    Ok(())
+}
+
+/// Returns the type of the message argument of a tell or ask handler.
+fn message_type(method: &ImplItemFn) -> Result<Box<Type>, Error> {
+    match method.sig.inputs.iter().nth(1) {
+        Some(FnArg::Typed(arg)) => Ok(arg.ty.clone()),
+        _ => Err(Error::new(method.sig.span(), "expected a message argument after self"))
+    }
+}
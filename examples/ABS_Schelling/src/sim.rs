@@ -66,7 +66,7 @@ impl Sim {
             })
             .on_tell::<ExecuteSimStep>(|msg, state, ctx| -> BehaviorAction<Sim> {
                 state.received_stepdone_messages = 0;
-                ctx.broadcast_tell(ExecuteStep{});
+                ctx.publish(SIM_STEP_TOPIC, ExecuteStep{});
                 Behavior::keep()
             })
             .build();
@@ -90,6 +90,8 @@ impl Person {
         };
         let mut person_behavior = BehaviorBuilder::new()
             .on_start(|state: &mut Person, ctx| {
+                // only persons take part in sim steps, thus they subscribe to them
+                ctx.subscribe::<ExecuteStep>(SIM_STEP_TOPIC).expect("person has not been spawned on an actor system!");
                 // get initial position
                 state.grid_actor.ask(GetInitialPos {race: state.population_type }, ctx.get_addr());
             })
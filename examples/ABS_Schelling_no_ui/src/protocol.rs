@@ -21,6 +21,9 @@ pub struct GridSnapshot {
     pub grid: Box<GridT>
 }
 
+/// Topic on which the sim publishes ExecuteStep messages to all persons.
+pub const SIM_STEP_TOPIC: &str = "sim_step";
+
 #[derive(Clone)]
 pub struct ExecuteStep {}
 pub struct StepDone {}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+use aector::actor::{ActorContext, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{ActorManageMessage, Behavior, BehaviorAction};
+use aector::Addr;
+
+// state of the actor, the behavior is generated from the annotated methods below
+struct Counter {
+    count: i32
+}
+
+// messages
+struct Add(i32);
+struct Get;
+
+#[aector::actor]
+impl Counter {
+    #[on_start]
+    fn start(&mut self, _ctx: &mut ActorContext) {
+        println!("Counter started with count {}", self.count);
+    }
+
+    #[tell]
+    fn add(&mut self, msg: Add, _ctx: &mut ActorContext) -> BehaviorAction<Counter> {
+        self.count += msg.0;
+        Behavior::keep()
+    }
+
+    #[ask]
+    fn get(&mut self, _msg: Get, reply_to: Addr, _ctx: &mut ActorContext) -> BehaviorAction<Counter> {
+        reply_to.tell(self.count);
+        Behavior::keep()
+    }
+
+    #[on_stop]
+    fn stop(&mut self, _ctx: &mut ActorContext) {
+        println!("Counter stopped with count {}", self.count);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let actor = Counter { count: 0 }.into_actor(MailboxType::Unbounded);
+    let addr = actor.get_addr();
+
+    let actor_sys = ActorSystem::new();
+    actor_sys.spawn(actor, "counter".to_owned()).unwrap();
+
+    addr.tell(Add(1));
+    addr.tell(Add(2));
+    addr.tell_delayed(ActorManageMessage::Kill, Duration::from_millis(100));
+    actor_sys.start().await;
+}
@@ -5,7 +5,7 @@ use aector::actor::{Actor, MailboxType};
 use aector::actor_system::ActorSystem;
 use aector::behavior::{ActorManageMessage, Behavior, BehaviorBuilder, BehaviorAction, StateCheckMessage};
 use aector::{Addr, Message};
-use aector::testing::{MessageType, Response, TestActor, ActorTestBuilder};
+use aector::testing::{MessageType, Response, TestActor, ActorTestBuilder, TestProbe, ActorTestError};
 use crate::Direction::{DOWN, UP};
 
 fn main() {}
@@ -85,9 +85,10 @@ mod tests {
             .tell(ActorManageMessage::Kill)
             .build();
 
-        // get test result: true = test passed, false = test failed
+        // get test result: a report of all executed steps if the test passed, otherwise a failure
+        // naming the failed task
         let test_res = sys.spawn_test(test_actor).await;
-        assert_eq!(test_res, true);
+        assert!(test_res.is_ok());
 
         sys.start().await;
 
@@ -120,10 +121,115 @@ async fn simple_actor_test() {
         .tell(ActorManageMessage::Kill)
         .build();
 
-    let test_res = sys.spawn_test(test_actor).await;
-    assert_eq!(test_res, true);
+    let report = sys.spawn_test(test_actor).await.unwrap();
+    assert_eq!(report.steps.len(), 5);
 
     // start actorsystem to run actors
     sys.start().await;
 }
 
+#[tokio::test]
+async fn probe_test() {
+    // the probe is used as collaborator of the tested actor, which doubles numbers and forwards questions to it
+    let mut collaborator = TestProbe::new();
+    let behavior = BehaviorBuilder::new()
+        .on_tell::<i32>(|msg, collaborator: &mut Addr, ctx| -> BehaviorAction<Addr> {
+            collaborator.tell(msg * 2);
+            Behavior::keep()
+        })
+        .on_ask::<String>(|msg, collaborator, reply_to, ctx| -> BehaviorAction<Addr> {
+            collaborator.ask(msg, reply_to);
+            Behavior::keep()
+        })
+        .build();
+    let actor = Actor::new(collaborator.addr(), behavior, MailboxType::Unbounded);
+    let addr = actor.get_addr();
+
+    let sys = ActorSystem::new();
+    sys.spawn(actor, "actor to be tested".to_string());
+
+    let timeout = Duration::from_secs(1);
+    addr.tell(1);
+    addr.tell(2);
+    assert_eq!(collaborator.expect_msg::<i32>(timeout).await.unwrap(), 2);
+    collaborator.expect_msg_matching(timeout, |msg: &i32| *msg == 4).await.unwrap();
+
+    addr.tell(3);
+    addr.tell(4);
+    let received = collaborator.receive_n(2, timeout).await.unwrap();
+    assert_eq!(received[1].downcast_ref::<i32>(), Some(&8));
+
+    // the probe answers questions forwarded to it in place of the collaborator
+    let mut client = TestProbe::new();
+    addr.ask("ping".to_string(), client.addr());
+    collaborator.expect_msg_matching(timeout, |msg: &String| msg == "ping").await.unwrap();
+    collaborator.reply_to_last("pong".to_string()).unwrap();
+    assert_eq!(client.expect_msg::<String>(timeout).await.unwrap(), "pong");
+
+    collaborator.expect_no_msg(Duration::from_millis(50)).await.unwrap();
+    assert!(client.reply_to_last(0).is_err());
+}
+
+#[tokio::test]
+async fn failing_actor_test() {
+    // the actor echoes strings, never answers numbers and answers bytes with a bool
+    let behavior = BehaviorBuilder::new()
+        .on_ask::<String>(|msg, state: &mut (), reply_to, ctx| -> BehaviorAction<()> {
+            reply_to.tell(msg);
+            Behavior::keep()
+        })
+        .on_ask::<i32>(|msg, state, reply_to, ctx| -> BehaviorAction<()> {
+            Behavior::keep()
+        })
+        .on_ask::<u8>(|_msg, _state, reply_to, _ctx| -> BehaviorAction<()> {
+            reply_to.tell(true);
+            Behavior::keep()
+        })
+        .build();
+    let actor = Actor::new((), behavior, MailboxType::Unbounded);
+    let addr = actor.get_addr();
+
+    let sys = ActorSystem::new();
+    sys.spawn(actor, "actor to be tested".to_string());
+
+    let test_actor = ActorTestBuilder::<()>::new(addr.clone())
+        .ask("ECHO".to_string(), Response::Tell(|msg: String| msg == "ECHO"))
+        .ask("RANDOM".to_string(), Response::Tell(|msg: String| msg == "ECHO"))
+        .build();
+    let failure = sys.spawn_test(test_actor).await.unwrap_err();
+    assert_eq!(failure.task, "Ask #1");
+    assert_eq!(failure.reason, ActorTestError::CriteriaNotMet);
+    assert_eq!(failure.received, Some("alloc::string::String"));
+    assert_eq!(failure.steps, vec!["Ask #0: sent alloc::string::String", "Ask #0: received alloc::string::String", "Ask #1: sent alloc::string::String"]);
+
+    let test_actor = ActorTestBuilder::<()>::new(addr.clone())
+        .ask(42, Response::Tell(|msg: i32| msg == 42))
+        .timeout(Duration::from_millis(50))
+        .build();
+    let failure = sys.spawn_test(test_actor).await.unwrap_err();
+    assert_eq!(failure.task, "Ask #0");
+    assert_eq!(failure.reason, ActorTestError::Timeout);
+    assert_eq!(failure.expected, Some("i32"));
+
+    // the test actor has no handler for bool, the reply fails the test right away instead of timing out
+    let test_actor = ActorTestBuilder::<()>::new(addr.clone())
+        .ask(1u8, Response::Tell(|msg: u8| msg == 1))
+        .build();
+    let failure = sys.spawn_test(test_actor).await.unwrap_err();
+    assert_eq!(failure.task, "Ask #0");
+    assert_eq!(failure.reason, ActorTestError::InvalidMessageOrder);
+    assert_eq!(failure.expected, Some("u8"));
+    assert_eq!(failure.received, Some("bool"));
+
+    // a panicking criteria function aborts the test, but the executed steps are still reported
+    let test_actor = ActorTestBuilder::<()>::new(addr)
+        .ask("ECHO".to_string(), Response::Tell(|msg: String| msg == "ECHO"))
+        .ask("ECHO".to_string(), Response::Tell(|_msg: String| -> bool { panic!("criteria panicked") }))
+        .build();
+    let failure = sys.spawn_test(test_actor).await.unwrap_err();
+    assert_eq!(failure.task, "Ask #1");
+    assert_eq!(failure.reason, ActorTestError::Aborted);
+    assert_eq!(failure.steps, vec!["Ask #0: sent alloc::string::String", "Ask #0: received alloc::string::String", "Ask #1: sent alloc::string::String"]);
+    // the test actors have been removed from the actor system
+    assert_eq!(sys.list().len(), 1);
+}
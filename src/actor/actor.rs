@@ -1,19 +1,27 @@
+use std::any::{Any, TypeId};
 use std::error::Error;
-use std::sync::Arc;
-use crate::actor::actor_context::{ActorContext, ContextFlag};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::{info_span, warn};
+use crate::actor::actor_context::{ActorContext, ActorId, ContextFlag};
 use crate::actor::backup::Backup;
 use crate::actor::mailbox::Mailbox;
-use crate::actor_system::ActorSystem;
-use crate::address::Addr;
+use crate::actor_system::{ActorSystem, DeadLetter, DEAD_LETTERS_TOPIC};
+use crate::address::{Addr, AskAddr, TellAddr, TypedAddr};
 use crate::behavior::Behavior;
-use crate::message::Message;
+use crate::message::{Envelope, Message};
+use crate::watchdog::{StallDetected, WatchdogError};
 
 /// ExitReason passed on to ActorSystem.
 #[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
 pub enum ExitReason {
     Kill,
     Restart,
-    Error
+    Error,
+    /// The [ActorSystem] the actor was running on has been stopped.
+    Shutdown
 }
 
 // static lifetime on S: no problem since actor has to be 'static anyways (i.e. contain no external refs)
@@ -27,17 +35,98 @@ pub struct Actor<S: Send + 'static> {
     behavior: Behavior<S>,
     mailbox: Mailbox,
     addr: Addr,
-    context: ActorContext
+    context: ActorContext,
+    tags: Vec<String>,
+    status: Arc<ActorStatus>,
+    /// Error with which the actor has last exited if the [Watchdog](crate::watchdog::Watchdog)
+    /// detected it as stalled.
+    watchdog_error: Option<WatchdogError>
 }
 
-/// Represents the capacity of the FIFO queue used for the mailbox of the actor.
+/// Run state of an actor which is shared with its [ActorSystem] for inspection.
+pub(crate) struct ActorStatus {
+    /// Name or id of the current behavior of the actor.
+    behavior: Mutex<String>,
+    /// True if the current behavior of the actor answers state snapshot requests.
+    state_snapshots: AtomicBool,
+    /// Number of times the actor has been restarted by its supervision strategy.
+    restarts: AtomicU64
+}
+
+impl ActorStatus {
+    fn new<S: Send>(behavior: &Behavior<S>) -> Self {
+        Self {
+            behavior: Mutex::new(behavior.label()),
+            state_snapshots: AtomicBool::new(behavior.has_state_snapshots()),
+            restarts: AtomicU64::new(0)
+        }
+    }
+
+    fn set_behavior<S: Send>(&self, behavior: &Behavior<S>) {
+        *self.behavior.lock().unwrap() = behavior.label();
+        self.state_snapshots.store(behavior.has_state_snapshots(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn has_state_snapshots(&self) -> bool {
+        self.state_snapshots.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn behavior(&self) -> String {
+        self.behavior.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record_restart(&self) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn restarts(&self) -> u64 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+}
+
+/// Maps a [Message] to its priority in a [MailboxType::Priority] mailbox, where messages with a
+/// higher priority are handled first.
+pub type PriorityFn = fn(&Message) -> u32;
+
+/// Kind of queue used for the mailbox of a registered actor as reported by
+/// [ActorSystem.list()](crate::actor_system::ActorSystem#method.list). Unlike [MailboxType] it
+/// does not carry the priority function, such that it can be compared and printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MailboxKind {
+    /// Bounded FIFO queue with the given capacity.
+    Bounded(usize),
+    /// Unbounded FIFO queue.
+    Unbounded,
+    /// Unbounded queue ordered by the priority of the messages.
+    Priority
+}
+
+/// Represents the kind of queue used for the mailbox of the actor.
+#[non_exhaustive]
 pub enum MailboxType {
-    /// Bounded queue where the given usize equals the maximal number of messages which can be kept
-    /// in the mailbox. Messages which arrive after the mailbox has reached its capacity are silently dropped.
+    /// Bounded FIFO queue where the given usize equals the maximal number of messages which can be kept
+    /// in the mailbox. Messages which arrive after the mailbox has reached its capacity are enqueued
+    /// in the background once there is capacity again. On
+    /// [deterministic](crate::actor_system::ActorSystem#method.deterministic) actor systems they are
+    /// dropped and reported as [DeadLetter](crate::actor_system::DeadLetter) instead, since the order in which waiting messages get capacity is not
+    /// deterministic.
     Bounded(usize),
-    /// Unbounded queue where the only upper limit of number of messages which can be stored is the
+    /// Unbounded FIFO queue where the only upper limit of number of messages which can be stored is the
     /// available memory.
-    Unbounded
+    Unbounded,
+    /// Unbounded queue where messages are ordered by the priority assigned to them by the given
+    /// function. Messages with equal priority are handled in FIFO order.
+    /// [ActorManageMessage](crate::behavior::ActorManageMessage)'s always jump the queue regardless of
+    /// their priority. Example:
+    /// ```
+    /// use aector::actor::MailboxType;
+    /// use aector::Message;
+    ///
+    /// struct Urgent;
+    /// let mailbox_type = MailboxType::Priority(|msg: &Message| if msg.is::<Urgent>() { 1 } else { 0 });
+    /// ```
+    Priority(PriorityFn)
 }
 
 impl<S: Send + 'static> Actor<S> {
@@ -51,6 +140,9 @@ impl<S: Send + 'static> Actor<S> {
             MailboxType::Unbounded => {
                 mailbox = Mailbox::unbounded();
             }
+            MailboxType::Priority(priority) => {
+                mailbox = Mailbox::priority(priority);
+            }
         }
 
         let addr = mailbox.get_addr();
@@ -58,21 +150,131 @@ impl<S: Send + 'static> Actor<S> {
         let ctx = ActorContext::new(addr.clone());
         Self {
             state,
+            status: Arc::new(ActorStatus::new(&behavior)),
             behavior,
             mailbox: mailbox,
             addr: addr,
-            context: ctx
+            context: ctx,
+            tags: Vec::new(),
+            watchdog_error: None
         }
     }
 
+    /// Adds the given tag to the actor. Once the actor is spawned, all actors with the same tag can be
+    /// addressed as group, e.g. using [ActorSystem.broadcast_tell_to()](crate::actor_system::ActorSystem#method.broadcast_tell_to).
+    /// An actor can have any number of tags.
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        if !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_string());
+        }
+        self
+    }
+
+    /// Returns the run state of the actor which is shared with its actor system.
+    pub(crate) fn status(&self) -> Arc<ActorStatus> {
+        self.status.clone()
+    }
+
+    pub(crate) fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Returns the maximal number of messages the mailbox of the actor can hold, [Option::None]
+    /// for an unbounded mailbox.
+    pub(crate) fn mailbox_capacity(&self) -> Option<usize> {
+        self.mailbox.capacity()
+    }
+
+    /// Returns the kind of queue used for the mailbox of the actor.
+    pub(crate) fn mailbox_kind(&self) -> MailboxKind {
+        self.mailbox.kind()
+    }
+
+    /// Returns the [WatchdogError] if the actor has last exited because the
+    /// [Watchdog](crate::watchdog::Watchdog) detected it as stalled, such that a
+    /// [SupervisionStrategy](crate::supervision::SupervisionStrategy) can tell stalls apart from
+    /// failed handlers.
+    pub fn watchdog_error(&self) -> Option<&WatchdogError> {
+        self.watchdog_error.as_ref()
+    }
+
+    /// Returns the tags of the actor.
+    pub fn get_tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
     fn handle(&mut self, m: Message) -> Option<Box<dyn Error>> {
         // handle message
-        let res = self.behavior.handle(m, &mut self.state, &mut self.context);
+        // the span of the handler is linked to the span in which the message was sent
+        let span = info_span!(
+            parent: m.envelope().span(),
+            "handle_message",
+            actor = self.context.label(),
+            actor_id = %self.context.id(),
+            message_type = m.type_name(),
+            kind = if m.is_ask() { "ask" } else { "tell" },
+            message_id = %m.envelope().id(),
+            correlation_id = %m.envelope().correlation_id()
+        );
+        let _entered = span.enter();
+
+        if let Some(stall) = m.downcast_ref::<StallDetected>() {
+            // the watchdog requests supervision for this actor as if its handler had failed
+            self.watchdog_error = Some(WatchdogError::Stalled(stall.0));
+            return Some(Box::new(WatchdogError::Stalled(stall.0)));
+        }
+
+        #[cfg(feature = "recording")]
+        if let Some(recorder) = self.context.system().and_then(|sys| sys.recorder()) {
+            recorder.record(self.context.name(), &m);
+        }
+
+        let (message_type, is_ask, handled) = (m.type_name(), m.is_ask(), self.behavior.handles_message(&m));
+        // dead letters are not reported for dead letters, such that subscribers without handler do not loop
+        let is_dead_letter = m.is::<DeadLetter>();
+
+        let start = Instant::now();
+        self.addr.stats().handling_started();
+        // all messages sent while handling this message inherit its envelope
+        let envelope = m.envelope().clone();
+        let behavior = &mut self.behavior;
+        let state = &mut self.state;
+        let context = &mut self.context;
+        let res = Envelope::scope(envelope, || behavior.handle(m, state, context));
+        let elapsed = start.elapsed();
+        self.addr.stats().record_handled(elapsed);
+
+        if let Some(threshold) = self.context.system().and_then(|sys| sys.watchdog()).and_then(|w| w.slow_handler_threshold()) {
+            if elapsed > threshold {
+                warn!("Handler took {}ms which exceeds the threshold of {}ms", elapsed.as_millis(), threshold.as_millis());
+            }
+        }
+
+        if let Some(sys) = self.context.system() {
+            if !handled && !is_dead_letter {
+                sys.publish(DEAD_LETTERS_TOPIC, DeadLetter {
+                    recipient: self.context.name().unwrap_or("anonymous").to_string(),
+                    message_type,
+                    is_ask
+                });
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some(sys) = self.context.system() {
+            if handled {
+                sys.metrics().record_handled(self.context.label(), message_type, elapsed);
+            } else {
+                // messages without handler are dropped
+                sys.metrics().record_dead_letters(1);
+            }
+        }
 
         match res {
             Ok(new_behavior) => {
                 // if user-defined handler defines a new behavior set behavior of actor to this new behavior
                 if let Some(new_behavior) = new_behavior {
+                    self.status.set_behavior(&new_behavior);
                     self.behavior = new_behavior;
                 }
                 None
@@ -84,46 +286,99 @@ impl<S: Send + 'static> Actor<S> {
     }
 
     fn on_start(&mut self) {
-        if let Some(f) = self.behavior.on_start {
+        if let Some(f) = &self.behavior.on_start {
             f(&mut self.state, &mut self.context);
         }
     }
 
     fn on_error(&mut self) {
-        if let Some(f) = self.behavior.on_error {
+        if let Some(f) = &self.behavior.on_error {
             f(&mut self.state, &mut self.context);
         }
     }
 
     fn on_kill(&mut self) {
-        if let Some(f) = self.behavior.on_kill {
+        if let Some(f) = &self.behavior.on_kill {
             f(&mut self.state, &mut self.context);
         }
     }
 
     fn on_restart(&mut self) {
-        if let Some(f) = self.behavior.on_restart {
+        if let Some(f) = &self.behavior.on_restart {
+            f(&mut self.state, &mut self.context);
+        }
+    }
+
+    fn on_stop(&mut self) {
+        if let Some(f) = &self.behavior.on_stop {
             f(&mut self.state, &mut self.context);
         }
     }
 
 
     pub(crate) async fn run(&mut self) -> ExitReason {
+        self.watchdog_error = None;
+        let exit_reason = self.run_loop().await;
+        // subscriptions do not survive the actor, a restarted actor has to subscribe again
+        self.context.unsubscribe_everything();
+        exit_reason
+    }
+
+    async fn run_loop(&mut self) -> ExitReason {
+        let scheduler = self.context.system().and_then(|sys| sys.scheduler()).cloned();
+        let id = self.context.id();
+
+        // on deterministic actor systems the actor waits for its turn before starting, unless it
+        // is restarted during its current turn
+        if let Some(scheduler) = &scheduler {
+            if !scheduler.is_holding(id) {
+                tokio::select! {
+                    biased;
+                    _ = self.context.shutdown_requested() => {
+                        return ExitReason::Shutdown;
+                    }
+                    _ = scheduler.turn(id) => {}
+                }
+            }
+        }
+
         self.on_start();
         loop {
             match self.context.flag {
                 ContextFlag::Run => {
-                    if let Some(msg) = self.mailbox.recv().await {
-                        // run handler for message and check for error in closure
-                        if let Some(_err) = self.handle(msg) {
-                            self.on_error();
-                            // propagate error up to actor_system for supervision strategy - we dont care what type of error occured
-                            return ExitReason::Error;
+                    if let Some(scheduler) = &scheduler {
+                        tokio::select! {
+                            biased;
+                            _ = self.context.shutdown_requested() => {
+                                self.on_stop();
+                                return ExitReason::Shutdown;
+                            }
+                            _ = scheduler.turn(id) => {}
+                        }
+                    }
+                    tokio::select! {
+                        // a stop of the actor system takes precedence over any pending messages
+                        biased;
+                        _ = self.context.shutdown_requested() => {
+                            self.on_stop();
+                            return ExitReason::Shutdown;
+                        }
+                        msg = self.mailbox.recv() => {
+                            if let Some(msg) = msg {
+                                // run handler for message and check for error in closure
+                                if let Some(_err) = self.handle(msg) {
+                                    self.on_error();
+                                    self.on_stop();
+                                    // propagate error up to actor_system for supervision strategy - we dont care what type of error occured
+                                    return ExitReason::Error;
+                                }
+                            }
                         }
                     }
                 }
                 ContextFlag::Kill => {
                     self.on_kill();
+                    self.on_stop();
                     return ExitReason::Kill;
                 },
                 ContextFlag::Restart => {
@@ -134,13 +389,74 @@ impl<S: Send + 'static> Actor<S> {
         }
     }
 
+    /// Returns the unique id of the actor.
+    pub fn get_id(&self) -> ActorId {
+        self.context.id()
+    }
+
     /// Returns the actors address.
     pub fn get_addr(&self) -> Addr {
         self.addr.clone()
     }
 
+    /// Returns the actors address typed with the protocol P if the current behavior of the actor
+    /// defines both an ask and a tell handler for messages of type P, otherwise [Option::None].
+    /// Use [get_tell_addr()](Actor#method.get_tell_addr) or [get_ask_addr()](Actor#method.get_ask_addr)
+    /// for actors which only handle one of both.
+    pub fn get_typed_addr<P: Any + Send>(&self) -> Option<TypedAddr<P>> {
+        let type_id = TypeId::of::<P>();
+        if self.behavior.handles_tell(type_id) && self.behavior.handles_ask(type_id) {
+            Some(TypedAddr::new(self.addr.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the actors address typed with the protocol P, which can only be used for sending
+    /// messages with tell, if the current behavior of the actor defines a tell handler for messages
+    /// of type P, otherwise [Option::None].
+    pub fn get_tell_addr<P: Any + Send>(&self) -> Option<TellAddr<P>> {
+        if self.behavior.handles_tell(TypeId::of::<P>()) {
+            Some(TellAddr::new(self.addr.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the actors address typed with the protocol P, which can only be used for sending
+    /// messages with ask, if the current behavior of the actor defines an ask handler for messages
+    /// of type P, otherwise [Option::None].
+    pub fn get_ask_addr<P: Any + Send>(&self) -> Option<AskAddr<P>> {
+        if self.behavior.handles_ask(TypeId::of::<P>()) {
+            Some(AskAddr::new(self.addr.clone()))
+        } else {
+            None
+        }
+    }
+
+
+    /// Sets the name under which the actor is registered.
+    pub(crate) fn set_name(&mut self, name: &str) {
+        self.context.set_name(name);
+    }
+
+    /// Sets the name of the pool this actor is part of.
+    pub(crate) fn set_pool(&mut self, pool: &str) {
+        self.context.set_pool(pool);
+    }
+
+    /// Sets the name of the actor which spawned this actor.
+    pub(crate) fn set_parent(&mut self, parent: Option<String>) {
+        self.context.set_parent(parent);
+    }
+
+    /// Returns the name of the actor which spawned this actor, see [ActorContext.parent()](ActorContext#method.parent).
+    pub(crate) fn get_parent(&self) -> Option<String> {
+        self.context.parent().map(|parent| parent.to_string())
+    }
 
     pub(crate) fn set_actor_sys(&mut self, sys: Arc<ActorSystem>) {
+        self.addr.bind_system(&sys);
         self.context.set_actor_sys(sys);
     }
 
@@ -160,5 +476,6 @@ impl<S: Send + 'static + Clone> Actor<S> {
     pub(crate) fn apply_backup(&mut self, backup: &Backup<S>) {
         self.state = backup.get_state();
         self.behavior = backup.get_behavior();
+        self.status.set_behavior(&self.behavior);
     }
 }
@@ -1,14 +1,46 @@
 use std::any::Any;
+use std::fmt;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
-use tokio::time::sleep;
+use tokio::sync::watch;
 use crate::actor::actor::Actor;
+use crate::actor::MailboxMetrics;
 
 use crate::actor_system::{ActorSystem, ActorSystemError};
-use crate::address::Addr;
+use crate::address::{ActorRef, Addr};
+use crate::clock::spawn_delayed;
+use crate::message::Envelope;
 use crate::supervision::SupervisionStrategy;
 
+/// Source of the unique ids of actors.
+static NEXT_ACTOR_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Unique id of an [Actor]. Every [Actor] is assigned a new id on creation, which stays the same
+/// for its whole lifetime including restarts by a [SupervisionStrategy].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ActorId(u64);
+
+impl ActorId {
+    /// Returns a new id which has not been used by any other actor.
+    pub(crate) fn next() -> Self {
+        ActorId(NEXT_ACTOR_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the numerical value of this id.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ActorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 #[derive(Clone, Copy)]
 /// Represents the internal run state of an actor.
 pub(crate) enum ContextFlag {
@@ -20,37 +52,132 @@ pub(crate) enum ContextFlag {
 /// This struct represents the actors internal properties such as its address, the current run state
 /// and holds a shared reference to its parent actor system for spawning new actors.
 pub struct ActorContext {
+    id: ActorId,
+    /// Name under which the actor is registered, [Option::None] for actors which are not registered.
+    name: Option<String>,
+    /// Name of the actor which spawned this actor from within its handlers, if any.
+    parent: Option<String>,
+    /// Name of the pool whose router forwards messages to this actor, if any.
+    pool: Option<String>,
     addr: Addr,
     pub(crate) flag: ContextFlag,
-    sys: Option<Arc<ActorSystem>>
+    sys: Option<Arc<ActorSystem>>,
+    shutdown: Option<watch::Receiver<bool>>
 }
 
 impl ActorContext {
 
     pub(crate) fn new(addr: Addr) -> Self {
         Self {
+            id: ActorId::next(),
+            name: None,
+            parent: None,
+            pool: None,
             addr,
             flag: ContextFlag::Run,
-            sys: None
+            sys: None,
+            shutdown: None
         }
     }
 
+    /// Returns the unique id of this actor.
+    pub fn id(&self) -> ActorId {
+        self.id
+    }
+
+    /// Returns the name under which this actor is registered in its actor system. Returns
+    /// [Option::None] if the actor has not been spawned yet or is part of a pool.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub(crate) fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    pub(crate) fn set_pool(&mut self, pool: &str) {
+        self.pool = Some(pool.to_string());
+    }
+
+    /// Returns the label of this actor in metrics and traces, which is its name or the name of its
+    /// pool for actors of a pool.
+    pub(crate) fn label(&self) -> &str {
+        self.name.as_deref().or(self.pool.as_deref()).unwrap_or("anonymous")
+    }
+
+    /// Returns the name of the actor which spawned this actor using one of the spawn functions of its
+    /// [ActorContext]. Returns [Option::None] for actors which have been spawned on the actor system directly.
+    pub fn parent(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: Option<String>) {
+        self.parent = parent;
+    }
+
+    /// Returns a snapshot of the load of the mailbox of this actor.
+    pub fn mailbox_metrics(&self) -> MailboxMetrics {
+        self.addr.metrics()
+    }
+
+    /// Returns the [Envelope] of the message which is currently handled by this actor, or
+    /// [Option::None] if called outside of a message handler, e.g. in on_start.
+    pub fn current_envelope(&self) -> Option<Envelope> {
+        Envelope::current()
+    }
+
+    /// Sets a header on the envelope of the message which is currently handled by this actor. The
+    /// header is propagated to all messages which are sent by this actor while handling the message
+    /// and transitively to all messages sent while handling those. Does nothing if called outside of
+    /// a message handler.
+    pub fn set_header(&mut self, key: &str, value: &str) {
+        Envelope::set_current_header(key.to_string(), value.to_string());
+    }
+
     /// Sets the internal reference to the parents ActorSystem
     pub(crate) fn set_actor_sys(&mut self, sys: Arc<ActorSystem>) {
         // this handler is called once the actor has been spawned on an actor_sys
+        self.shutdown = Some(sys.subscribe_shutdown());
         self.sys = Some(sys);
     }
 
+    /// Returns the ActorSystem this actor has been spawned on.
+    pub(crate) fn system(&self) -> Option<&Arc<ActorSystem>> {
+        self.sys.as_ref()
+    }
+
+    /// Completes once the parents ActorSystem has been stopped. Never completes if this actor has
+    /// not been spawned on any actor system yet.
+    pub(crate) async fn shutdown_requested(&mut self) {
+        match &mut self.shutdown {
+            None => {
+                std::future::pending::<()>().await;
+            }
+            Some(rx) => {
+                // an error means that the actor system has been dropped, which also ends this actor
+                let _ = rx.wait_for(|stopped| *stopped).await;
+            }
+        }
+    }
+
+    /// Removes all subscriptions of this actor from the event bus of the parents ActorSystem.
+    pub(crate) fn unsubscribe_everything(&self) {
+        if let Some(sys) = &self.sys {
+            sys.event_bus().unsubscribe_all(self.id);
+        }
+    }
+
     /// Spawns the given [Actor] on the [ActorSystem] of this [Actor].
     /// This function works identically to ActorSystem.spawn, but can be called from
     /// within an actors handler without reference to the ActorSystem.
-    pub fn spawn<S: Send + 'static>(&mut self, actor: Actor<S>, name: String) -> Result<(), ActorSystemError> {
+    pub fn spawn<S: Send + 'static>(&mut self, mut actor: Actor<S>, name: String) -> Result<ActorRef, ActorSystemError> {
         match &self.sys {
             None => {
                 // actor cant spawn other actors with this actor has not been spawned on any actor system yet
                 Err(ActorSystemError::ActorNotSpawnedYet)
             }
             Some(sys) => {
+                actor.set_parent(self.name.clone());
                 sys.spawn(actor, name)
             }
         }
@@ -59,18 +186,62 @@ impl ActorContext {
     /// Spawns the given actor on the actor system of this actor with the given supervision strategy.
     /// This function works identically to [ActorSystem#method.spawn_with_supervision], but can be called from
     /// within an actors handler without needing a reference to the [ActorSystem].
-    pub fn spawn_with_supervision<S: Send + Clone>(self: &Arc<Self>, actor: Actor<S>, supervision_strategy: Box<dyn SupervisionStrategy<S> + Send>, name: String) -> Result<(), ActorSystemError> {
+    pub fn spawn_with_supervision<S: Send + Clone>(self: &Arc<Self>, mut actor: Actor<S>, supervision_strategy: Box<dyn SupervisionStrategy<S> + Send>, name: String) -> Result<ActorRef, ActorSystemError> {
         match &self.sys {
             None => {
                 // actor cant spawn other actors if this actor has not been spawned on any actor system yet
                 Err(ActorSystemError::ActorNotSpawnedYet)
             }
             Some(sys) => {
+                actor.set_parent(self.name.clone());
                 sys.spawn_with_supervision(actor, supervision_strategy, name)
             }
         }
     }
 
+    /// Spawns the given [Actor] on the [ActorSystem] of this [Actor] under a unique name derived from its id.
+    /// This function works identically to [ActorSystem.spawn_anonymous()](ActorSystem#method.spawn_anonymous),
+    /// but can be called from within an actors handler without reference to the ActorSystem.
+    pub fn spawn_anonymous<S: Send + 'static>(&mut self, mut actor: Actor<S>) -> Result<ActorRef, ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                actor.set_parent(self.name.clone());
+                Ok(sys.spawn_anonymous(actor))
+            }
+        }
+    }
+
+    /// Watches the given actor, such that this actor receives a [Terminated](crate::actor_system::Terminated)
+    /// notification once the watched actor has been removed from the actor system. If the watched
+    /// actor has already been removed, the notification is sent immediately.
+    pub fn watch(&self, actor: &ActorRef) -> Result<(), ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                sys.watch(self.id, self.addr.clone(), actor);
+                Ok(())
+            }
+        }
+    }
+
+    /// Stops watching the given actor.
+    pub fn unwatch(&self, actor: &ActorRef) -> Result<(), ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                sys.unwatch(self.id, actor);
+                Ok(())
+            }
+        }
+    }
+
     /// Queries this actors actor system for another actor with the given name. Returns the [Addr]
     /// of the sought for actor if it exists.
     pub fn query(&self, name: &str) -> Option<Addr> {
@@ -121,10 +292,106 @@ impl ActorContext {
     /// Runs the given function after a given delay. This function does not block the handlers flow
     /// and may run even after the handlers scope has been exited.
     pub fn run_delayed(&self, f: Box<dyn Fn() -> () + Send>, delay: Duration) {
-        tokio::spawn(async move {
-            sleep(delay).await;
-            f();
-        });
+        // delays are measured by the clock of the actor system
+        match &self.sys {
+            None => spawn_delayed(delay, Box::new(f)),
+            Some(sys) => sys.clock().schedule(delay, Box::new(f))
+        }
+    }
+
+    /// Sends given message to all [Actor]'s with the given tag which are run on this [ActorSystem]
+    /// without specifying a reply_to [Addr](crate::address::Addr).
+    pub fn broadcast_tell_to<M: Send + Any + Clone>(&self, tag: &str, msg: M) -> Result<(), ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                sys.broadcast_tell_to(tag, msg);
+                Ok(())
+            }
+        }
+    }
+
+    /// Queries this actors actor system for all actors with the given tag.
+    pub fn query_by_tag(&self, tag: &str) -> Vec<Addr> {
+        match &self.sys {
+            None => {
+                Vec::new()
+            }
+            Some(sys) => {
+                sys.query_by_tag(tag)
+            }
+        }
+    }
+
+    /// Subscribes this actor to messages of type M which are published on the given topic. All
+    /// subscriptions of an actor are removed once it exits.
+    pub fn subscribe<M: Send + Any>(&self, topic: &str) -> Result<(), ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                sys.event_bus().subscribe::<M>(topic, self.id, self.addr.clone());
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes the subscription of this actor to messages of type M on the given topic.
+    pub fn unsubscribe<M: Send + Any>(&self, topic: &str) -> Result<(), ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                sys.event_bus().unsubscribe::<M>(topic, self.id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Subscribes this actor to all published messages of type M regardless of their topic. All
+    /// subscriptions of an actor are removed once it exits.
+    pub fn subscribe_type<M: Send + Any>(&self) -> Result<(), ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                sys.event_bus().subscribe_type::<M>(self.id, self.addr.clone());
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes the subscription of this actor to all published messages of type M.
+    pub fn unsubscribe_type<M: Send + Any>(&self) -> Result<(), ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                sys.event_bus().unsubscribe_type::<M>(self.id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Publishes the given message on the given topic of the [ActorSystem] of this [Actor].
+    /// This function works identically to [ActorSystem#method.publish], but can be called from
+    /// within an actors handler without needing a reference to the [ActorSystem].
+    pub fn publish<M: Send + Any + Clone>(&self, topic: &str, msg: M) -> Result<(), ActorSystemError> {
+        match &self.sys {
+            None => {
+                Err(ActorSystemError::ActorNotSpawnedYet)
+            }
+            Some(sys) => {
+                sys.publish(topic, msg);
+                Ok(())
+            }
+        }
     }
 
     /// Sends given message to all [Actor]'s which are run on this [ActorSystem] without
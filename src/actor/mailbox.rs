@@ -1,10 +1,17 @@
-use tokio::sync::mpsc;
+use std::cmp::{Ordering as CmpOrdering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, Notify};
 use tokio::sync::mpsc::{Receiver, UnboundedReceiver};
 use crate::{Addr, Message};
+use crate::actor::{MailboxKind, PriorityFn};
 
 enum Queue {
     Bounded(Receiver<Message>),
-    Unbounded(UnboundedReceiver<Message>)
+    Unbounded(UnboundedReceiver<Message>),
+    Priority(PriorityReceiver)
 }
 
 impl Queue {
@@ -16,13 +23,251 @@ impl Queue {
             Queue::Unbounded(rx) => {
                 rx.recv().await
             }
+            Queue::Priority(rx) => {
+                rx.queue.recv().await
+            }
+        }
+    }
+}
+
+/// Entry of a [PriorityQueue]. Entries are ordered by their priority and then by their arrival, such
+/// that messages of equal priority are handled in FIFO order.
+struct PriorityEntry {
+    priority: u32,
+    seq: Reverse<u64>,
+    msg: Message
+}
+
+impl PriorityEntry {
+    fn key(&self) -> (u32, Reverse<u64>) {
+        (self.priority, self.seq)
+    }
+}
+
+impl PartialEq for PriorityEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for PriorityEntry {}
+
+impl PartialOrd for PriorityEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PriorityEntry {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Unbounded queue which is shared between a priority mailbox and all [Addr]'s pointing to it.
+/// Messages with a higher priority are received first.
+pub(crate) struct PriorityQueue {
+    heap: Mutex<BinaryHeap<PriorityEntry>>,
+    priority: PriorityFn,
+    next_seq: AtomicU64,
+    /// Notifies the single receiver about new messages.
+    notify: Notify,
+    closed: AtomicBool
+}
+
+impl PriorityQueue {
+    fn new(priority: PriorityFn) -> Self {
+        Self {
+            heap: Mutex::new(BinaryHeap::new()),
+            priority,
+            next_seq: AtomicU64::new(0),
+            notify: Notify::new(),
+            closed: AtomicBool::new(false)
+        }
+    }
+
+    /// Adds the given message to the queue. Returns false if the receiver does not exist anymore.
+    pub(crate) fn push(&self, msg: Message) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+        let entry = PriorityEntry {
+            priority: (self.priority)(&msg),
+            seq: Reverse(self.next_seq.fetch_add(1, Ordering::Relaxed)),
+            msg
+        };
+        self.heap.lock().unwrap().push(entry);
+        self.notify.notify_one();
+        true
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    async fn recv(&self) -> Option<Message> {
+        loop {
+            if let Some(entry) = self.heap.lock().unwrap().pop() {
+                return Some(entry.msg);
+            }
+            // a notification sent before this point is stored, so no message can be missed
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// Receiving side of a [PriorityQueue] which closes the queue once the mailbox is dropped.
+struct PriorityReceiver {
+    queue: Arc<PriorityQueue>
+}
+
+impl Drop for PriorityReceiver {
+    fn drop(&mut self) {
+        self.queue.closed.store(true, Ordering::Relaxed);
+        self.queue.heap.lock().unwrap().clear();
+    }
+}
+
+/// Returns the current time in nanoseconds since the unix epoch.
+fn unix_nanos() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64
+}
+
+/// Snapshot of the load of the mailbox of an actor, see [Addr.metrics()](crate::Addr#method.metrics).
+#[derive(Clone, Debug)]
+pub struct MailboxMetrics {
+    /// Number of messages which are currently waiting in the mailbox.
+    pub len: usize,
+    /// Maximal number of messages of bounded mailboxes, [Option::None] for unbounded ones.
+    pub capacity: Option<usize>,
+    /// Total number of messages which have been put into the mailbox.
+    pub received: u64,
+    /// Total number of messages which have been handled by the actor.
+    pub handled: u64,
+    /// Point in time at which the actor has finished handling its last message, [Option::None] if
+    /// it has not handled any message yet.
+    pub last_handled: Option<SystemTime>
+}
+
+/// Counters of a mailbox which are shared between the mailbox, the actor handling its messages
+/// and all [Addr]'s pointing to it.
+pub(crate) struct MailboxStats {
+    /// Maximal number of messages for bounded mailboxes, [Option::None] for unbounded ones.
+    capacity: Option<usize>,
+    /// Number of messages which are currently waiting in the mailbox.
+    queued: AtomicUsize,
+    /// Number of system messages which are currently waiting in the control lane of the mailbox.
+    control_queued: AtomicUsize,
+    /// Number of messages which have been put into the mailbox.
+    received: AtomicU64,
+    /// Number of messages which have been handled by the actor.
+    handled: AtomicU64,
+    /// Total time the actor spent handling messages.
+    handling_nanos: AtomicU64,
+    /// Time at which the last message has been handled in nanoseconds since the unix epoch, 0 if none has been handled.
+    last_handled_nanos: AtomicU64,
+    /// Time at which the currently running handler has been started in nanoseconds since the unix
+    /// epoch, 0 if no handler is running.
+    handling_since_nanos: AtomicU64
+}
+
+impl MailboxStats {
+    pub(crate) fn new(capacity: Option<usize>) -> Self {
+        Self {
+            capacity,
+            queued: AtomicUsize::new(0),
+            control_queued: AtomicUsize::new(0),
+            received: AtomicU64::new(0),
+            handled: AtomicU64::new(0),
+            handling_nanos: AtomicU64::new(0),
+            last_handled_nanos: AtomicU64::new(0),
+            handling_since_nanos: AtomicU64::new(0)
+        }
+    }
+
+    pub(crate) fn enqueued(&self) {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dequeued(&self) {
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn control_enqueued(&self) {
+        self.control_queued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn control_dequeued(&self) {
+        self.control_queued.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn received(&self) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn handling_started(&self) {
+        self.handling_since_nanos.store(unix_nanos(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_handled(&self, duration: Duration) {
+        self.handled.fetch_add(1, Ordering::Relaxed);
+        self.handling_nanos.fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+        self.last_handled_nanos.store(unix_nanos(), Ordering::Relaxed);
+        self.handling_since_nanos.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns the start of the currently running handler as identifier of the invocation and
+    /// the time it has been running for, or [Option::None] if no handler is running.
+    pub(crate) fn handling_since(&self) -> Option<(u64, Duration)> {
+        match self.handling_since_nanos.load(Ordering::Relaxed) {
+            0 => None,
+            started => Some((started, Duration::from_nanos(unix_nanos().saturating_sub(started))))
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    pub(crate) fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of messages waiting in the mailbox including system messages.
+    pub(crate) fn pending(&self) -> usize {
+        self.queued() + self.control_queued.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn handled(&self) -> u64 {
+        self.handled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn handling_time(&self) -> Duration {
+        Duration::from_nanos(self.handling_nanos.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn metrics(&self) -> MailboxMetrics {
+        let last_handled = match self.last_handled_nanos.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(UNIX_EPOCH + Duration::from_nanos(nanos))
+        };
+        MailboxMetrics {
+            len: self.queued(),
+            capacity: self.capacity,
+            received: self.received.load(Ordering::Relaxed),
+            handled: self.handled(),
+            last_handled
         }
     }
 }
 
 pub(crate) struct Mailbox {
     queue: Queue,
-    addr: Addr
+    /// Separate lane for system messages such as [ActorManageMessage](crate::behavior::ActorManageMessage)'s,
+    /// which is always unbounded and polled before the queue.
+    control: UnboundedReceiver<Message>,
+    addr: Addr,
+    stats: Arc<MailboxStats>
 }
 
 impl Mailbox {
@@ -30,29 +275,147 @@ impl Mailbox {
     pub(crate) fn bounded(buffer_size: usize) -> Self {
         let (tx, rx) = mpsc::channel(buffer_size);
         let queue = Queue::Bounded(rx);
-        let addr = Addr::bounded(tx);
+        let (control_tx, control) = mpsc::unbounded_channel();
+        let stats = Arc::new(MailboxStats::new(Some(buffer_size)));
+        let addr = Addr::bounded(tx, control_tx, stats.clone());
         Mailbox {
             queue,
-            addr
+            control,
+            addr,
+            stats
         }
     }
 
     pub(crate) fn unbounded() -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
         let queue = Queue::Unbounded(rx);
-        let addr = Addr::unbounded(tx);
+        let (control_tx, control) = mpsc::unbounded_channel();
+        let stats = Arc::new(MailboxStats::new(None));
+        let addr = Addr::unbounded(tx, control_tx, stats.clone());
         Mailbox {
             queue,
-            addr
+            control,
+            addr,
+            stats
         }
     }
 
+    pub(crate) fn priority(priority: PriorityFn) -> Self {
+        let queue = Arc::new(PriorityQueue::new(priority));
+        let (control_tx, control) = mpsc::unbounded_channel();
+        let stats = Arc::new(MailboxStats::new(None));
+        let addr = Addr::priority(queue.clone(), control_tx, stats.clone());
+        Mailbox {
+            queue: Queue::Priority(PriorityReceiver { queue }),
+            control,
+            addr,
+            stats
+        }
+    }
+
+    /// Receives the next message of this mailbox. Messages on the control lane are always received
+    /// before pending messages of the queue.
     pub(crate) async fn recv(&mut self) -> Option<Message> {
-        self.queue.recv().await
+        tokio::select! {
+            biased;
+            msg = self.control.recv() => {
+                if msg.is_some() {
+                    self.stats.control_dequeued();
+                }
+                msg
+            }
+            msg = self.queue.recv() => {
+                if msg.is_some() {
+                    self.stats.dequeued();
+                }
+                msg
+            }
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> Option<usize> {
+        self.stats.capacity()
+    }
+
+    pub(crate) fn kind(&self) -> MailboxKind {
+        match &self.queue {
+            Queue::Bounded(rx) => MailboxKind::Bounded(rx.max_capacity()),
+            Queue::Unbounded(_) => MailboxKind::Unbounded,
+            Queue::Priority(_) => MailboxKind::Priority
+        }
     }
 
     pub(crate) fn get_addr(&self) -> Addr {
         self.addr.clone()
     }
 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::Message;
+    use crate::behavior::ActorManageMessage;
+
+    use super::Mailbox;
+
+    /// Messages of type u32 have their value as priority, all other messages the lowest priority.
+    fn priority(msg: &Message) -> u32 {
+        msg.downcast_ref::<u32>().copied().unwrap_or(0)
+    }
+
+    async fn recv_u32(mailbox: &mut Mailbox) -> u32 {
+        *mailbox.recv().await.unwrap().downcast::<u32>()
+    }
+
+    async fn recv_str(mailbox: &mut Mailbox) -> &'static str {
+        *mailbox.recv().await.unwrap().downcast::<&'static str>()
+    }
+
+    #[tokio::test]
+    async fn priority_mailbox_receives_higher_priority_first() {
+        let mut mailbox = Mailbox::priority(priority);
+        let addr = mailbox.get_addr();
+        for msg in [1u32, 5, 3, 4, 2] {
+            addr.tell(msg);
+        }
+        for expected in [5u32, 4, 3, 2, 1] {
+            assert_eq!(recv_u32(&mut mailbox).await, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn priority_mailbox_is_fifo_within_equal_priority() {
+        let mut mailbox = Mailbox::priority(priority);
+        let addr = mailbox.get_addr();
+        for msg in ["first", "second", "third"] {
+            addr.tell(msg);
+        }
+        addr.tell(1u32);
+        addr.tell("fourth");
+        assert_eq!(recv_u32(&mut mailbox).await, 1);
+        for expected in ["first", "second", "third", "fourth"] {
+            assert_eq!(recv_str(&mut mailbox).await, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn manage_messages_jump_the_priority_queue() {
+        let mut mailbox = Mailbox::priority(priority);
+        let addr = mailbox.get_addr();
+        addr.tell(u32::MAX);
+        addr.tell(ActorManageMessage::Kill);
+        let msg = mailbox.recv().await.unwrap();
+        assert!(matches!(*msg.downcast::<ActorManageMessage>(), ActorManageMessage::Kill));
+        assert_eq!(recv_u32(&mut mailbox).await, u32::MAX);
+    }
+
+    #[tokio::test]
+    async fn manage_messages_sent_through_data_lane_keep_their_order() {
+        let mut mailbox = Mailbox::unbounded();
+        let addr = mailbox.get_addr();
+        addr.tell(1u32);
+        addr.send_data(Message::without_sender(ActorManageMessage::Kill));
+        assert_eq!(recv_u32(&mut mailbox).await, 1);
+        let msg = mailbox.recv().await.unwrap();
+        assert!(matches!(*msg.downcast::<ActorManageMessage>(), ActorManageMessage::Kill));
+    }
+}
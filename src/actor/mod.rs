@@ -32,7 +32,10 @@ mod backup;
 mod actor_context;
 mod mailbox;
 
-pub use actor::{Actor, ExitReason, MailboxType};
+pub use actor::{Actor, ExitReason, MailboxKind, MailboxType, PriorityFn};
+pub(crate) use actor::ActorStatus;
 pub use backup::Backup;
-pub use actor_context::{ActorContext};
+pub use actor_context::{ActorContext, ActorId};
+pub use mailbox::MailboxMetrics;
+pub(crate) use mailbox::{Mailbox, MailboxStats, PriorityQueue};
 
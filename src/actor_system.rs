@@ -2,18 +2,30 @@
 
 use std::any::Any;
 use std::fmt::{Debug};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use dashmap::DashMap;
 use thiserror::Error;
-use tokio::task::JoinHandle;
+use tokio::sync::watch;
+use tokio::task::{AbortHandle, JoinHandle};
 use tokio::time::sleep;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 
-use crate::actor::{Actor, ExitReason};
-use crate::address::Addr;
-use crate::message::BroadcastMessage;
+use crate::actor::{Actor, ActorId, ActorStatus, ExitReason, Mailbox, MailboxKind, MailboxMetrics};
+use crate::address::{ActorRef, Addr};
+use crate::event_bus::EventBus;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+#[cfg(feature = "recording")]
+use crate::recording::Recorder;
+use crate::behavior::{StateSnapshot, StateSnapshotRequest};
+use crate::clock::Clock;
+use crate::message::{BroadcastMessage, Message};
+use crate::routing::{Resizer, Router, RoutingLogic};
+use crate::scheduler::Scheduler;
 use crate::supervision::{SuperVisionAction, SupervisionStrategy};
-use crate::testing::TestActor;
+use crate::watchdog::Watchdog;
+use crate::testing::{TestActor, TestFailure, TestReport};
 
 /// The [ActorSystem] represents a collection of [Actor]'s which can communicate with each other. All
 /// [Actor]'s are registered in the [ActorSystem] with a unique actor name and are executed by it.
@@ -39,17 +51,273 @@ use crate::testing::TestActor;
 ///
 /// ```
 pub struct ActorSystem {
-    registry: DashMap<String, Addr>,
-    join_handles: Mutex<Vec<JoinHandle<()>>>
+    registry: DashMap<String, RegistryEntry>,
+    /// Watchers of each actor, which are notified with [Terminated] once the actor is removed.
+    watchers: DashMap<ActorId, Vec<(ActorId, Addr)>>,
+    event_bus: EventBus,
+    shutdown: watch::Sender<bool>,
+    /// Tasks running the actors, which are aborted if they do not stop in time once the actor system is stopped.
+    tasks: Mutex<Vec<AbortHandle>>,
+    watchdog: OnceLock<Watchdog>,
+    #[cfg(feature = "metrics")]
+    metrics: Metrics,
+    #[cfg(feature = "recording")]
+    recorder: OnceLock<Recorder>,
+    /// Clock which measures all delays of the actor system.
+    clock: Arc<Clock>,
+    /// Scheduler of deterministic actor systems, see [ActorSystem::deterministic()](ActorSystem#method.deterministic).
+    scheduler: Option<Arc<Scheduler>>
+}
+
+/// Time actors get to stop once the actor system is stopped with [ActorSystem.stop()](ActorSystem#method.stop).
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Topic on which [ActorRegistered] and [ActorUnregistered] events are published. Actors can either
+/// subscribe to this topic or to the event types themselves using [ActorContext.subscribe_type()](crate::actor::ActorContext#method.subscribe_type).
+pub const REGISTRY_TOPIC: &str = "registry";
+
+/// Topic on which [DeadLetter] events are published.
+pub const DEAD_LETTERS_TOPIC: &str = "dead-letters";
+
+/// Event which is published whenever an [Actor] drops a message without handling it because its
+/// current [Behavior](crate::behavior::Behavior) does not define a handler for it, or a message is
+/// dropped because the bounded mailbox of its recipient is full on a
+/// [deterministic](ActorSystem#method.deterministic) actor system.
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    /// Name of the actor which dropped the message.
+    pub recipient: String,
+    pub message_type: &'static str,
+    /// True if the message was sent using ask.
+    pub is_ask: bool
+}
+
+/// Event which is published whenever an [Actor] is registered in an [ActorSystem].
+#[derive(Clone)]
+pub struct ActorRegistered {
+    pub name: String,
+    pub addr: Addr
+}
+
+/// Event which is published whenever an [Actor] is removed from an [ActorSystem].
+#[derive(Clone, Debug)]
+pub struct ActorUnregistered {
+    pub name: String
+}
+
+/// Notification which is sent to all watchers of an [Actor] once it has been removed from its
+/// [ActorSystem], see [ActorContext.watch()](crate::actor::ActorContext#method.watch). Like
+/// [ActorManageMessage](crate::behavior::ActorManageMessage)'s it is sent through the control lane
+/// of the watchers mailbox and handled by a tell handler for [Terminated].
+#[derive(Clone, Debug)]
+pub struct Terminated {
+    pub name: String,
+    pub id: ActorId
+}
+
+/// Describes an [Actor] which is registered in an [ActorSystem] as returned by [ActorSystem.list()](ActorSystem#method.list).
+#[derive(Clone, Debug)]
+pub struct ActorInfo {
+    pub name: String,
+    /// Name of the type of the state of the actor.
+    pub state_type: &'static str,
+    /// Maximal number of messages the mailbox of the actor can hold, [Option::None] for an unbounded mailbox.
+    pub mailbox_capacity: Option<usize>,
+    /// Kind of queue used for the mailbox of the actor.
+    pub mailbox_type: MailboxKind,
+    /// True if the actor has been spawned with a [SupervisionStrategy].
+    pub supervised: bool,
+    /// Time since the actor has been spawned.
+    pub uptime: Duration,
+    pub tags: Vec<String>
+}
+
+/// Load of an [Actor] which is registered in an [ActorSystem] as returned by [ActorSystem.stats()](ActorSystem#method.stats).
+#[derive(Clone, Debug)]
+pub struct ActorStats {
+    pub name: String,
+    pub id: ActorId,
+    pub mailbox: MailboxMetrics
+}
+
+/// Snapshot of an [Actor] which is registered in an [ActorSystem] as part of a [SystemDump].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ActorDump {
+    pub name: String,
+    pub id: ActorId,
+    /// Name of the actor which spawned this actor from within its handlers, if any.
+    pub parent: Option<String>,
+    /// Names of all registered actors which have been spawned by this actor, sorted by name.
+    pub children: Vec<String>,
+    /// Name of the current [Behavior](crate::behavior::Behavior) of the actor or its id if it is
+    /// unnamed. [Option::None] for routers of pools.
+    pub behavior: Option<String>,
+    /// Number of messages waiting in the mailbox of the actor.
+    pub mailbox_depth: usize,
+    /// Number of times the actor has been restarted by its [SupervisionStrategy].
+    pub restarts: u64,
+    /// Time since the actor has been spawned.
+    pub uptime: Duration,
+    /// [Debug] representation of the state of the actor, only included by
+    /// [ActorSystem.dump_with_states()](ActorSystem#method.dump_with_states) for actors which have
+    /// enabled state snapshots.
+    pub state: Option<String>
+}
+
+/// Snapshot of all [Actor]'s which are registered in an [ActorSystem] as returned by
+/// [ActorSystem.dump()](ActorSystem#method.dump). With the `json` feature the dump can be
+/// serialized to JSON.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct SystemDump {
+    /// All registered actors sorted by name.
+    pub actors: Vec<ActorDump>
+}
+
+impl SystemDump {
+    /// Returns the dump of the actor with the given name.
+    pub fn get(&self, name: &str) -> Option<&ActorDump> {
+        self.actors.iter().find(|actor| actor.name == name)
+    }
+
+    /// Serializes this dump to pretty printed JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        // serializing plain strings and numbers can not fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+/// Represents an [Actor] which is registered in an [ActorSystem].
+struct RegistryEntry {
+    id: ActorId,
+    addr: Addr,
+    tags: Vec<String>,
+    state_type: &'static str,
+    mailbox_capacity: Option<usize>,
+    mailbox_type: MailboxKind,
+    supervised: bool,
+    spawned_at: Instant,
+    parent: Option<String>,
+    /// Run state shared with the actor, [Option::None] for routers of pools.
+    status: Option<Arc<ActorStatus>>
+}
+
+impl RegistryEntry {
+    fn for_actor<S: Send>(actor: &Actor<S>, supervised: bool) -> Self {
+        Self {
+            id: actor.get_id(),
+            addr: actor.get_addr(),
+            tags: actor.get_tags(),
+            state_type: std::any::type_name::<S>(),
+            mailbox_capacity: actor.mailbox_capacity(),
+            mailbox_type: actor.mailbox_kind(),
+            supervised,
+            spawned_at: Instant::now(),
+            parent: actor.get_parent(),
+            status: Some(actor.status())
+        }
+    }
+
+    fn for_router(id: ActorId, mailbox: &Mailbox) -> Self {
+        Self {
+            id,
+            addr: mailbox.get_addr(),
+            tags: Vec::new(),
+            state_type: std::any::type_name::<Router>(),
+            mailbox_capacity: mailbox.capacity(),
+            mailbox_type: mailbox.kind(),
+            supervised: false,
+            spawned_at: Instant::now(),
+            parent: None,
+            status: None
+        }
+    }
+
+    fn dump(&self, name: &str) -> ActorDump {
+        ActorDump {
+            name: name.to_string(),
+            id: self.id,
+            parent: self.parent.clone(),
+            children: Vec::new(),
+            behavior: self.status.as_ref().map(|status| status.behavior()),
+            mailbox_depth: self.addr.mailbox_len(),
+            restarts: self.status.as_ref().map(|status| status.restarts()).unwrap_or(0),
+            uptime: self.spawned_at.elapsed(),
+            state: None
+        }
+    }
+
+    fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    fn info(&self, name: &str) -> ActorInfo {
+        ActorInfo {
+            name: name.to_string(),
+            state_type: self.state_type,
+            mailbox_capacity: self.mailbox_capacity,
+            mailbox_type: self.mailbox_type,
+            supervised: self.supervised,
+            uptime: self.spawned_at.elapsed(),
+            tags: self.tags.clone()
+        }
+    }
+}
+
+/// Returns true if the given name matches the given glob pattern, where `*` matches any sequence of
+/// characters and `?` matches exactly one character.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // position of the last * in the pattern and the position in name it currently covers up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            // let the last * cover one more character
+            p = star_p + 1;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 #[derive(Error, Debug)]
 /// This enum represents different errors which can occur when using [ActorSystem].
+#[non_exhaustive]
 pub enum ActorSystemError {
     #[error("An actor with the same name already exists in the registry!")]
     ActorNameAlreadyInUse,
+    #[error("Actor names starting with $ are reserved for actors spawned by the actor system!")]
+    ReservedActorName,
     #[error("This actor has not been spawned yet!")]
-    ActorNotSpawnedYet
+    ActorNotSpawnedYet,
+    #[error("A pool has to consist of at least one actor!")]
+    EmptyPool,
+    #[error("The minimum size of a resizable pool must not be greater than its maximum size!")]
+    InvalidPoolBounds,
+    #[error("The watchdog of this actor system has already been started!")]
+    WatchdogAlreadyStarted,
+    #[error("The recording of this actor system has already been started!")]
+    RecordingAlreadyStarted,
+    #[error("This actor system has not been created with ActorSystem::deterministic()!")]
+    NotDeterministic,
+    #[error("The clock of this actor system is not a manual clock!")]
+    NotManualClock
 }
 
 impl ActorSystem {
@@ -57,47 +325,110 @@ impl ActorSystem {
     #[instrument]
     /// Creates a new empty [ActorSystem].
     pub fn new() -> Arc<Self> {
+        Self::create(Arc::new(Clock::system()), None)
+    }
+
+    /// Creates a new empty [ActorSystem] which measures all delays with the given [Clock]. With a
+    /// [Clock::manual()] delayed messages and restarts are only due once the clock has been
+    /// advanced with [advance()](ActorSystem#method.advance).
+    pub fn with_clock(clock: Clock) -> Arc<Self> {
+        Self::create(Arc::new(clock), None)
+    }
+
+    /// Creates a new empty [ActorSystem] which executes its [Actor]'s deterministically: only one
+    /// actor handles a message at a time and the next actor is chosen among all actors with pending
+    /// messages by a random number generator seeded with the given seed. Delays of delayed messages
+    /// and restarts are measured in virtual time by a [Clock::manual()], which is advanced to the
+    /// next delay once no actor has pending messages. Running the same actors with the same seed yields the same interleaving of messages,
+    /// such that order dependent bugs can be reproduced and searched for by sweeping seeds.
+    ///
+    /// Actors only handle messages while [run_until_idle()](ActorSystem#method.run_until_idle) or
+    /// [start()](ActorSystem#method.start) is awaited, such that all messages sent from outside of
+    /// the actor system before are taken into account in the same way on every run. Pools with
+    /// [RoutingLogic::Random] draw from the same seeded random number generator. Messages sent to a
+    /// full [MailboxType::Bounded](crate::actor::MailboxType::Bounded) mailbox are dropped and
+    /// reported as [DeadLetter] instead of waiting for capacity. Resizable pools are resized in real
+    /// time and thus not deterministic.
+    ///
+    /// ```
+    /// use aector::actor_system::ActorSystem;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let actor_sys = ActorSystem::deterministic(42);
+    /// // spawn actors and send them their initial messages ...
+    /// actor_sys.run_until_idle().await.unwrap();
+    /// # }
+    /// ```
+    pub fn deterministic(seed: u64) -> Arc<Self> {
+        let clock = Arc::new(Clock::manual());
+        let scheduler = Scheduler::new(seed, clock.clone());
+        Self::create(clock, Some(Arc::new(scheduler)))
+    }
+
+    fn create(clock: Arc<Clock>, scheduler: Option<Arc<Scheduler>>) -> Arc<Self> {
         Arc::new(Self {
             registry: DashMap::new(),
-            join_handles: Mutex::new(Vec::new())
+            watchers: DashMap::new(),
+            event_bus: EventBus::new(),
+            shutdown: watch::channel(false).0,
+            tasks: Mutex::new(Vec::new()),
+            watchdog: OnceLock::new(),
+            #[cfg(feature = "metrics")]
+            metrics: Metrics::default(),
+            #[cfg(feature = "recording")]
+            recorder: OnceLock::new(),
+            clock,
+            scheduler
         })
     }
 
     /// Spawns a given [Actor] without a [SupervisionStrategy]. On error this actor will just exit.
+    /// Returns an [ActorRef] to the spawned actor. Names starting with `$` are reserved for actors
+    /// spawned with [spawn_anonymous()](ActorSystem#method.spawn_anonymous).
     #[instrument(skip(self, actor), fields(actor_name = %name))]
-    pub fn spawn<S: Send>(self: &Arc<Self>, mut actor: Actor<S>, name: String) -> Result<(), ActorSystemError> {
+    pub fn spawn<S: Send>(self: &Arc<Self>, actor: Actor<S>, name: String) -> Result<ActorRef, ActorSystemError> {
+        self.check_name(&name)?;
+        Ok(self.run_unsupervised(actor, name))
+    }
+
+    /// Spawns a given [Actor] without a [SupervisionStrategy] under a unique name derived from its
+    /// [ActorId](crate::actor::ActorId), such that callers do not have to come up with unique names.
+    pub fn spawn_anonymous<S: Send>(self: &Arc<Self>, actor: Actor<S>) -> ActorRef {
+        let name = format!("$anonymous-{}", actor.get_id());
+        self.run_unsupervised(actor, name)
+    }
+
+    /// Registers the given actor under the given name and runs it until it exits.
+    fn run_unsupervised<S: Send>(self: &Arc<Self>, mut actor: Actor<S>, name: String) -> ActorRef {
 
         let name_backup = name.clone();
+        let actor_ref = ActorRef::new(name.clone(), actor.get_id(), actor.get_addr());
 
-        // check if another actor with same name already exists in registry
-        if self.registry.contains_key(&name) {
-            error!("Actor with same name already exists in this actor system!");
-            return Err(ActorSystemError::ActorNameAlreadyInUse);
-        }
         // set reference in actor to actor_system
         actor.set_actor_sys(self.clone());
-        self.registry.insert(name, actor.get_addr());
+        actor.set_name(&name);
+        self.register(name, RegistryEntry::for_actor(&actor, false));
+        self.schedule_actor(actor.get_id(), actor.get_addr(), true);
 
         // Arc handle for passing on into future for removing actor from registry before killing actor
         let sys_ref = self.clone();
 
-        let run_handle = tokio::spawn(async move {
+        let task = tokio::spawn(async move {
             let actor_exit_reason = actor.run().await;
 
             match actor_exit_reason {
                 _ => {
                     info!("Actor without supervision died! Cleaning up resources and removing actor {} from system", &name_backup);
                     // remove actor from registry before exiting run loop
-                    sys_ref.registry.remove(&name_backup);
+                    sys_ref.unregister(&name_backup);
                     return;
                 }
             }
         });
-        // add to join_handles for proper shutdown
-        let mut join_h = self.join_handles.lock().unwrap();
-        join_h.push(run_handle);
+        self.track(&task);
 
-        Ok(())
+        actor_ref
     }
 
     /// Spawns a given [Actor] with a given [SupervisionStrategy] and a unique name. The [SupervisionStrategy]
@@ -107,67 +438,324 @@ impl ActorSystem {
     /// always implements [Clone] by default. The initial state and [Behavior](crate::behavior::Behavior) is
     /// stored in a [Backup](crate::actor::Backup).
     #[instrument(skip(self, actor, supervision_strategy), fields(actor_name = %name))]
-    pub fn spawn_with_supervision<S: Send + Clone>(self: &Arc<Self>, mut actor: Actor<S>, mut supervision_strategy: Box<dyn SupervisionStrategy<S> + Send>, name: String) -> Result<(), ActorSystemError> {
+    pub fn spawn_with_supervision<S: Send + Clone>(self: &Arc<Self>, mut actor: Actor<S>, mut supervision_strategy: Box<dyn SupervisionStrategy<S> + Send>, name: String) -> Result<ActorRef, ActorSystemError> {
 
-        // check if another actor with same name already exists in registry
-        if self.registry.contains_key(&name) {
-            error!("Actor with same name already exists in this actor system!");
-            return Err(ActorSystemError::ActorNameAlreadyInUse);
-        }
+        self.check_name(&name)?;
         // set reference in actor to actor_system
         actor.set_actor_sys(self.clone());
+        actor.set_name(&name);
 
         info!("Creating backup of actors initial state and behavior");
         // create backup of initial state and behavior
         let actor_backup = actor.create_backup();
 
         let name_backup = name.clone();
-        self.registry.insert(name, actor.get_addr());
+        let actor_ref = ActorRef::new(name.clone(), actor.get_id(), actor.get_addr());
+        self.register(name, RegistryEntry::for_actor(&actor, true));
+        self.schedule_actor(actor.get_id(), actor.get_addr(), true);
 
         // Arc handle for passing on into future for removing actor from registry before killing actor
         let sys_ref = self.clone();
 
-        let join_handle = tokio::spawn(async move {
+        // used for cancelling delayed restarts if the actor system is stopped in the meantime
+        let mut shutdown = self.subscribe_shutdown();
+
+        let task = tokio::spawn(async move {
             loop {
                 let actor_exit_reason = actor.run().await;
                 info!("Actor exited run loop with reason: {:?}", actor_exit_reason);
 
+                if let ExitReason::Shutdown = actor_exit_reason {
+                    // no supervision once the actor system is stopped
+                    info!("Cleaning up resources and removing actor {} from system", &name_backup);
+                    sys_ref.unregister(&name_backup);
+                    return;
+                }
+
                 let supervision_action = supervision_strategy.apply(actor_exit_reason, &actor_backup, &mut actor);
                 info!("Supervision action: {:?}", &supervision_action);
                 match supervision_action {
                     SuperVisionAction::Exit => {
                         info!("Cleaning up resources and removing actor {} from system", &name_backup);
                         // remove actor from registry before exiting run loop
-                        sys_ref.registry.remove(&name_backup);
+                        sys_ref.unregister(&name_backup);
                         return;
                     }
                     SuperVisionAction::Restart => {
                         info!("Trying to restart the actor with its initial state and behavior");
+                        actor.status().record_restart();
+                        #[cfg(feature = "metrics")]
+                        sys_ref.metrics.record_restart(&name_backup);
                         // just continue with infinite run loop
                     }
                     SuperVisionAction::RestartDelayed(delay) => {
                         info!("Trying to restart the actor with its initial state and behavior after a delay of {}ms", delay.as_millis());
-                        // async wait before continuing with run loop
-                        sleep(delay).await;
+                        actor.status().record_restart();
+                        #[cfg(feature = "metrics")]
+                        sys_ref.metrics.record_restart(&name_backup);
+                        // async wait on the clock of the actor system before continuing with run loop
+                        let id = actor.get_id();
+                        let restart_delay = async {
+                            match &sys_ref.scheduler {
+                                Some(scheduler) => scheduler.sleep(id, delay).await,
+                                None => sys_ref.clock.sleep(delay).await
+                            }
+                        };
+                        tokio::select! {
+                            _ = restart_delay => {}
+                            _ = shutdown.wait_for(|stopped| *stopped) => {
+                                info!("Actor system stopped during restart delay. Removing actor {} from system", &name_backup);
+                                sys_ref.unregister(&name_backup);
+                                return;
+                            }
+                        }
                     }
                 }
             }
         });
-        // add to join_handles for proper shutdown
-        let mut join_h = self.join_handles.lock().unwrap();
-        join_h.push(join_handle);
+        self.track(&task);
+        Ok(actor_ref)
+    }
+
+    /// Spawns a pool of size identical [Actor]'s created by the given factory without a
+    /// [SupervisionStrategy]. Only the router of the pool is registered under the given name, which
+    /// forwards each message it receives to one of the actors of the pool as decided by the given
+    /// [RoutingLogic]. The sender of ask messages is preserved, such that replies are sent directly
+    /// to the original sender. [ActorManageMessage](crate::behavior::ActorManageMessage)'s sent to the router are
+    /// forwarded to all actors of the pool. The router exits once it has been killed or all actors
+    /// of the pool have exited.
+    #[instrument(skip(self, factory, logic), fields(pool_name = %name))]
+    pub fn spawn_pool<S: Send + 'static, F>(self: &Arc<Self>, factory: F, size: usize, logic: RoutingLogic, name: String) -> Result<ActorRef, ActorSystemError>
+    where
+        F: Fn() -> Actor<S>
+    {
+        if size == 0 {
+            error!("Cannot spawn a pool without actors!");
+            return Err(ActorSystemError::EmptyPool);
+        }
+        self.check_name(&name)?;
+
+        let mut router = Router::new(logic);
+        for _ in 0..size {
+            router.add_routee(self.spawn_routee(factory(), &name));
+        }
+        Ok(self.spawn_router(router, name))
+    }
+
+    /// Spawns a pool of identical [Actor]'s created by the given factory like [spawn_pool()](ActorSystem#method.spawn_pool),
+    /// but the number of actors in the pool is adjusted at runtime by the given [Resizer] based on
+    /// the load of the pool. The pool starts with the minimum number of actors of the [Resizer].
+    #[instrument(skip(self, factory, resizer, logic), fields(pool_name = %name))]
+    pub fn spawn_resizable_pool<S: Send + 'static, F>(self: &Arc<Self>, factory: F, resizer: Resizer, logic: RoutingLogic, name: String) -> Result<ActorRef, ActorSystemError>
+    where
+        F: Fn() -> Actor<S> + Send + 'static
+    {
+        if resizer.min() > resizer.max() {
+            error!("Invalid bounds for resizable pool!");
+            return Err(ActorSystemError::InvalidPoolBounds);
+        }
+        self.check_name(&name)?;
+
+        let sys_ref = self.clone();
+        let pool = name.clone();
+        let spawner = Box::new(move || sys_ref.spawn_routee(factory(), &pool));
+        let router = Router::resizable(logic, resizer, spawner);
+        Ok(self.spawn_router(router, name))
+    }
+
+    /// Runs the given actor as routee of the pool with the given name. Routees are not registered,
+    /// they are only reachable through the router of the pool.
+    fn spawn_routee<S: Send + 'static>(self: &Arc<Self>, mut routee: Actor<S>, pool: &str) -> Addr {
+        routee.set_actor_sys(self.clone());
+        routee.set_pool(pool);
+        let addr = routee.get_addr();
+        let id = routee.get_id();
+        self.schedule_actor(id, addr.clone(), true);
+        let sys_ref = self.clone();
+        let task = tokio::spawn(async move {
+            routee.run().await;
+            sys_ref.unschedule_actor(id);
+        });
+        self.track(&task);
+        addr
+    }
+
+    /// Registers the given router under the given name and runs it.
+    fn spawn_router(self: &Arc<Self>, router: Router, name: String) -> ActorRef {
+        let mailbox = Mailbox::unbounded();
+        let id = ActorId::next();
+        let actor_ref = ActorRef::new(name.clone(), id, mailbox.get_addr());
+        mailbox.get_addr().bind_system(self);
+        self.register(name.clone(), RegistryEntry::for_router(id, &mailbox));
+        // the router does not need a turn for starting
+        self.schedule_actor(id, mailbox.get_addr(), false);
+
+        let sys_ref = self.clone();
+        let shutdown = self.subscribe_shutdown();
+        let scheduler = self.scheduler.clone().map(|scheduler| (scheduler, id));
+        let task = tokio::spawn(async move {
+            router.run(name.clone(), mailbox, shutdown, scheduler).await;
+            info!("Router of pool {} exited. Removing it from system", &name);
+            sys_ref.unregister(&name);
+        });
+        self.track(&task);
+        actor_ref
+    }
+
+    /// Starts the given [Watchdog], which checks all registered [Actor]'s for slow handlers and
+    /// stalled mailboxes until the actor system is stopped. Only one watchdog can be started per
+    /// actor system. Must be called from within a tokio runtime.
+    pub fn start_watchdog(self: &Arc<Self>, watchdog: Watchdog) -> Result<(), ActorSystemError> {
+        if self.watchdog.set(watchdog.clone()).is_err() {
+            error!("Watchdog has already been started!");
+            return Err(ActorSystemError::WatchdogAlreadyStarted);
+        }
+        tokio::spawn(crate::watchdog::run(self.clone(), watchdog));
         Ok(())
     }
 
-    /// Stops the execution of the actor system and all associated actors.
+    /// Starts recording all messages which are handled by the [Actor]'s of this actor system with
+    /// the given [Recorder], see [recording](crate::recording). Only one recording can be started per
+    /// actor system. Requires the `recording` feature.
+    #[cfg(feature = "recording")]
+    pub fn start_recording(&self, recorder: Recorder) -> Result<(), ActorSystemError> {
+        if self.recorder.set(recorder).is_err() {
+            error!("Recording has already been started!");
+            return Err(ActorSystemError::RecordingAlreadyStarted);
+        }
+        Ok(())
+    }
+
+    /// Returns the recorder of this actor system if recording has been started.
+    #[cfg(feature = "recording")]
+    pub(crate) fn recorder(&self) -> Option<&Recorder> {
+        self.recorder.get()
+    }
+
+    /// Returns the configuration of the watchdog if it has been started.
+    pub(crate) fn watchdog(&self) -> Option<&Watchdog> {
+        self.watchdog.get()
+    }
+
+    /// Returns the name, id and address of all registered actors except routers of pools.
+    pub(crate) fn watched_actors(&self) -> Vec<(String, ActorId, Addr)> {
+        self.registry.iter()
+            .filter(|entry| entry.state_type != std::any::type_name::<Router>())
+            .map(|entry| (entry.key().clone(), entry.id, entry.addr.clone()))
+            .collect()
+    }
+
+    /// Stops the execution of the actor system and all associated actors. Each actor finishes the
+    /// message it is currently handling, runs its on_stop action and is then removed from the
+    /// actor system. Once all actors have been removed, [start()](ActorSystem#method.start) returns.
+    /// Actors which are still running after a grace period of 5 seconds, e.g. because a handler
+    /// never returns, are aborted without running their on_stop action, see
+    /// [stop_with_grace_period()](ActorSystem#method.stop_with_grace_period).
     pub fn stop(self: &Arc<Self>) {
-        self.registry.clear();
-        let mut join_h = self.join_handles.lock().unwrap();
-        for jh in join_h.iter_mut() {
-            jh.abort();
+        self.stop_with_grace_period(STOP_GRACE_PERIOD);
+    }
+
+    /// Stops the execution of the actor system like [stop()](ActorSystem#method.stop), but aborts
+    /// all actors which are still running after the given grace period and removes them from the
+    /// actor system, such that [start()](ActorSystem#method.start) returns in any case.
+    pub fn stop_with_grace_period(self: &Arc<Self>, grace_period: Duration) {
+        self.shutdown.send_replace(true);
+        let sys = self.clone();
+        tokio::spawn(async move {
+            sleep(grace_period).await;
+            if sys.registry.is_empty() {
+                return;
+            }
+            error!("{} actors did not stop within {}ms. Aborting them", sys.registry.len(), grace_period.as_millis());
+            for task in sys.tasks.lock().unwrap().drain(..) {
+                task.abort();
+            }
+            let names: Vec<String> = sys.registry.iter().map(|entry| entry.key().clone()).collect();
+            for name in names {
+                sys.unregister(&name);
+            }
+        });
+    }
+
+    /// Keeps track of the given task running an actor, such that it can be aborted once the actor
+    /// system is stopped.
+    fn track<T>(&self, task: &JoinHandle<T>) {
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(task.abort_handle());
+    }
+
+    /// Returns the scheduler of this actor system if it has been created with
+    /// [deterministic()](ActorSystem#method.deterministic).
+    pub(crate) fn scheduler(&self) -> Option<&Arc<Scheduler>> {
+        self.scheduler.as_ref()
+    }
+
+    /// Returns the seed of the scheduler if this actor system has been created with
+    /// [deterministic()](ActorSystem#method.deterministic).
+    pub fn seed(&self) -> Option<u64> {
+        self.scheduler.as_ref().map(|scheduler| scheduler.seed())
+    }
+
+    /// Returns the virtual time which has passed since this actor system has been created if it
+    /// uses a [Clock::manual()], which is the case for actor systems created with
+    /// [deterministic()](ActorSystem#method.deterministic).
+    pub fn virtual_time(&self) -> Option<Duration> {
+        if self.clock.is_manual() { Some(self.clock.now()) } else { None }
+    }
+
+    /// Returns the [Clock] which measures all delays of this actor system.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Advances the [Clock::manual()] of this actor system by the given duration, such that all
+    /// delayed messages, delayed functions and restarts which are due in the meantime are fired in
+    /// order. The resulting messages are handled asynchronously by the [Actor]'s afterwards. Returns
+    /// [ActorSystemError::NotManualClock] if the actor system follows the real time.
+    pub fn advance(&self, duration: Duration) -> Result<(), ActorSystemError> {
+        if self.clock.advance(duration) {
+            Ok(())
+        } else {
+            Err(ActorSystemError::NotManualClock)
         }
     }
 
+    /// Lets the [Actor]'s of a deterministic actor system handle messages until none of them has
+    /// pending messages and no delayed message or restart is due, advancing the virtual time as
+    /// needed. Returns [ActorSystemError::NotDeterministic] for actor systems which have not been
+    /// created with [deterministic()](ActorSystem#method.deterministic).
+    pub async fn run_until_idle(&self) -> Result<(), ActorSystemError> {
+        match &self.scheduler {
+            None => {
+                Err(ActorSystemError::NotDeterministic)
+            }
+            Some(scheduler) => {
+                scheduler.run(true, &mut self.subscribe_shutdown()).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds the given actor to the scheduler of a deterministic actor system.
+    fn schedule_actor(&self, id: ActorId, addr: Addr, wants_turn: bool) {
+        if let Some(scheduler) = &self.scheduler {
+            scheduler.register(id, addr, wants_turn);
+        }
+    }
+
+    /// Removes the given actor from the scheduler of a deterministic actor system.
+    fn unschedule_actor(&self, id: ActorId) {
+        if let Some(scheduler) = &self.scheduler {
+            scheduler.deregister(id);
+        }
+    }
+
+    /// Returns a receiver which is notified once the actor system is stopped.
+    pub(crate) fn subscribe_shutdown(&self) -> watch::Receiver<bool> {
+        self.shutdown.subscribe()
+    }
+
     /// Starts the actor system. Note that this function is async and thus has to be .await-ed for
     /// the actor system to start.
     #[instrument(skip_all)]
@@ -175,12 +763,119 @@ impl ActorSystem {
         // note: this function is async since a "normal", non async loop would get optimized away in cargo run --release and cause havoc
         // this workaround (start().await) has the same effect but does not cause this problem
 
+        // deterministic actor systems only handle messages while their scheduler is run
+        if let Some(scheduler) = &self.scheduler {
+            scheduler.run(false, &mut self.subscribe_shutdown()).await;
+            return;
+        }
+
         // this just blocks forever such that the tokio runtime in main does not go out of scope and exit
         while self.registry.len() > 0 {
 
         }
     }
 
+    /// Checks whether the given name can be used for a new actor.
+    fn check_name(&self, name: &str) -> Result<(), ActorSystemError> {
+        if name.starts_with('$') {
+            error!("Actor names starting with $ are reserved!");
+            return Err(ActorSystemError::ReservedActorName);
+        }
+        // check if another actor with same name already exists in registry
+        if self.registry.contains_key(name) {
+            error!("Actor with same name already exists in this actor system!");
+            return Err(ActorSystemError::ActorNameAlreadyInUse);
+        }
+        Ok(())
+    }
+
+    /// Adds the given entry to the registry and publishes an [ActorRegistered] event.
+    fn register(&self, name: String, entry: RegistryEntry) {
+        let event = ActorRegistered {
+            name: name.clone(),
+            addr: entry.addr.clone()
+        };
+        self.registry.insert(name, entry);
+        self.event_bus.publish(REGISTRY_TOPIC, event);
+        #[cfg(feature = "metrics")]
+        self.metrics.record_spawned();
+    }
+
+    /// Removes the entry with the given name from the registry and publishes an [ActorUnregistered] event.
+    /// Also notifies all watchers of the removed actor.
+    fn unregister(&self, name: &str) {
+        if let Some((_, entry)) = self.registry.remove(name) {
+            self.unschedule_actor(entry.id);
+            self.event_bus.publish(REGISTRY_TOPIC, ActorUnregistered {
+                name: name.to_string()
+            });
+            #[cfg(feature = "metrics")]
+            {
+                self.metrics.record_stopped();
+                // messages remaining in the mailbox of the removed actor are never handled
+                self.metrics.record_dead_letters(entry.addr.mailbox_len() as u64);
+            }
+
+            if let Some((_, watchers)) = self.watchers.remove(&entry.id) {
+                for (_, watcher) in watchers {
+                    watcher.tell(Terminated {
+                        name: name.to_string(),
+                        id: entry.id
+                    });
+                }
+            }
+            // the removed actor does not watch any other actor anymore
+            for mut watchers in self.watchers.iter_mut() {
+                watchers.retain(|(watcher_id, _)| *watcher_id != entry.id);
+            }
+            self.watchers.retain(|_, watchers| !watchers.is_empty());
+        }
+    }
+
+    /// Notifies the given watcher with [Terminated] once the given actor has been removed from
+    /// this actor system. If the actor has already been removed, the watcher is notified immediately.
+    pub(crate) fn watch(&self, watcher_id: ActorId, watcher: Addr, target: &ActorRef) {
+        {
+            let mut watchers = self.watchers.entry(target.id()).or_default();
+            if !watchers.iter().any(|(id, _)| *id == watcher_id) {
+                watchers.push((watcher_id, watcher.clone()));
+            }
+        }
+
+        // the watcher is registered before checking the registry, such that the removal of the
+        // target in the meantime can not be missed
+        let alive = self.registry.get(target.name())
+            .map(|entry| entry.id == target.id())
+            .unwrap_or(false);
+        if !alive {
+            let was_watching = self.remove_watcher(watcher_id, target.id());
+            // otherwise the watcher has already been notified by unregister
+            if was_watching {
+                watcher.tell(Terminated {
+                    name: target.name().to_string(),
+                    id: target.id()
+                });
+            }
+        }
+    }
+
+    /// Stops notifying the given watcher about the removal of the given actor.
+    pub(crate) fn unwatch(&self, watcher_id: ActorId, target: &ActorRef) {
+        self.remove_watcher(watcher_id, target.id());
+    }
+
+    /// Removes the given watcher of the given actor. Returns true if it has been watching.
+    fn remove_watcher(&self, watcher_id: ActorId, target_id: ActorId) -> bool {
+        let mut removed = false;
+        if let Some(mut watchers) = self.watchers.get_mut(&target_id) {
+            let len = watchers.len();
+            watchers.retain(|(id, _)| *id != watcher_id);
+            removed = watchers.len() != len;
+        }
+        self.watchers.remove_if(&target_id, |_, watchers| watchers.is_empty());
+        removed
+    }
+
     /// Searches the [ActorSystem] for an [Actor] with the given name. If successful this function
     /// returns [Option::Some(Addr)](crate::address::Addr) of the sought for [Actor], otherwise [Option::None].
     pub fn query(self: &Arc<Self>, name: &str) -> Option<Addr> {
@@ -188,10 +883,147 @@ impl ActorSystem {
             None => {
                 None
             }
-            Some(addr) => {
-                Some(addr.clone())
+            Some(entry) => {
+                Some(entry.addr.clone())
+            }
+        }
+    }
+
+    /// Searches the [ActorSystem] for all [Actor]'s whose name matches the given glob pattern, where
+    /// `*` matches any sequence of characters and `?` matches exactly one character, e.g. `person-*`.
+    /// Returns the names and [Addr](crate::address::Addr)'s of all matching [Actor]'s sorted by name.
+    pub fn query_matching(&self, pattern: &str) -> Vec<(String, Addr)> {
+        let mut matches: Vec<(String, Addr)> = self.registry.iter()
+            .filter(|entry| glob_match(pattern, entry.key()))
+            .map(|entry| (entry.key().clone(), entry.addr.clone()))
+            .collect();
+        matches.sort_by(|a, b| a.0.cmp(&b.0));
+        matches
+    }
+
+    /// Returns a description of all [Actor]'s which are registered in this [ActorSystem] sorted by name.
+    pub fn list(&self) -> Vec<ActorInfo> {
+        let mut infos: Vec<ActorInfo> = self.registry.iter()
+            .map(|entry| entry.info(entry.key()))
+            .collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    /// Returns a snapshot of the mailbox load of all [Actor]'s which are registered in this
+    /// [ActorSystem] sorted by name. Actors of pools are only represented by their router.
+    pub fn stats(&self) -> Vec<ActorStats> {
+        let mut stats: Vec<ActorStats> = self.registry.iter()
+            .map(|entry| ActorStats {
+                name: entry.key().clone(),
+                id: entry.id,
+                mailbox: entry.addr.metrics()
+            })
+            .collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
+    /// Returns a snapshot of all [Actor]'s which are registered in this [ActorSystem] sorted by name,
+    /// including the tree of actors spawned by other actors. Actors of pools are only represented by
+    /// their router. States of actors are not included, see [dump_with_states()](ActorSystem#method.dump_with_states).
+    pub fn dump(&self) -> SystemDump {
+        let mut actors: Vec<ActorDump> = self.registry.iter()
+            .map(|entry| entry.dump(entry.key()))
+            .collect();
+        actors.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let children: Vec<(String, String)> = actors.iter()
+            .filter_map(|actor| actor.parent.clone().map(|parent| (parent, actor.name.clone())))
+            .collect();
+        for (parent, child) in children {
+            // actors are sorted by name, thus the children of each actor are as well
+            if let Some(actor) = actors.iter_mut().find(|actor| actor.name == parent) {
+                actor.children.push(child);
             }
         }
+
+        SystemDump {
+            actors
+        }
+    }
+
+    /// Works like [dump()](ActorSystem#method.dump), but additionally includes the [Debug]
+    /// representation of the state of every actor which has enabled state snapshots with
+    /// [BehaviorBuilder.enable_state_snapshots()](crate::behavior::BehaviorBuilder#method.enable_state_snapshots).
+    /// The snapshot request is handled through the control lane of the mailbox, i.e. before pending
+    /// messages. Actors which do not answer within the given timeout, e.g. because they are stuck in
+    /// a handler, are dumped without state.
+    pub async fn dump_with_states(&self, timeout: Duration) -> SystemDump {
+        let mut dump = self.dump();
+
+        let requests = dump.actors.iter()
+            .enumerate()
+            .filter_map(|(index, actor)| {
+                let entry = self.registry.get(&actor.name)?;
+                let status = entry.status.as_ref()?;
+                // the actor might have been replaced by another one with the same name in the meantime
+                if !status.has_state_snapshots() || entry.id != actor.id {
+                    return None;
+                }
+                Some((index, entry.addr.clone()))
+            })
+            .map(|(index, addr)| async move {
+                let mut reply_to = Mailbox::unbounded();
+                addr.ask(StateSnapshotRequest, reply_to.get_addr());
+                let state = match tokio::time::timeout(timeout, reply_to.recv()).await {
+                    Ok(Some(msg)) if msg.is::<StateSnapshot>() => Some(msg.downcast::<StateSnapshot>().0),
+                    _ => None
+                };
+                (index, state)
+            })
+            .collect::<Vec<_>>();
+
+        for (index, state) in futures::future::join_all(requests).await {
+            dump.actors[index].state = state;
+        }
+        dump
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Renders the metrics of this [ActorSystem] in the Prometheus text format: handled messages
+    /// and handler latencies per actor and message type, mailbox depths, restarts per actor, dead
+    /// letters and the number of spawned and stopped actors. The metrics of the actors of a pool are
+    /// labelled with the name of the pool. Requires the `metrics` feature.
+    #[cfg(feature = "metrics")]
+    pub fn render_metrics(&self) -> String {
+        let mut mailbox_depths: Vec<(String, usize)> = self.registry.iter()
+            .map(|entry| (entry.key().clone(), entry.addr.mailbox_len()))
+            .collect();
+        mailbox_depths.sort();
+        self.metrics.render(&mailbox_depths)
+    }
+
+    /// Serves the output of [render_metrics()](ActorSystem#method.render_metrics) at
+    /// `http://127.0.0.1:<port>/metrics` until the actor system is stopped. Port 0 binds to any free
+    /// port. Returns the address the endpoint is bound to. Requires the `metrics` feature.
+    #[cfg(feature = "metrics")]
+    pub async fn serve_metrics(self: &Arc<Self>, port: u16) -> std::io::Result<std::net::SocketAddr> {
+        let (listener, addr) = crate::metrics::bind(port).await?;
+        info!("Serving metrics on http://{}/metrics", addr);
+        let sys = self.clone();
+        tokio::spawn(crate::metrics::serve(listener, move || sys.render_metrics(), self.subscribe_shutdown()));
+        Ok(addr)
+    }
+
+    /// Starts the given [AdminConsole](crate::admin::AdminConsole), which accepts commands on its
+    /// Unix socket until the actor system is stopped. Requires the `admin` feature. Must be called
+    /// from within a tokio runtime.
+    #[cfg(all(feature = "admin", unix))]
+    pub fn start_admin(self: &Arc<Self>, console: crate::admin::AdminConsole) -> std::io::Result<()> {
+        let listener = crate::admin::bind(&console)?;
+        info!("Serving admin console on {}", console.path().display());
+        tokio::spawn(crate::admin::serve(self.clone(), console, listener));
+        Ok(())
     }
 
     /// Sends given message to all [Actor]'s which are run on this [ActorSystem] without
@@ -199,8 +1031,8 @@ impl ActorSystem {
     pub fn broadcast_tell<M: Send + Any + Clone>(&self, msg: M) {
         let broadcast_msg = BroadcastMessage::without_sender(msg);
 
-        for addr in self.registry.iter_mut() {
-            addr.send(broadcast_msg.get_message());
+        for entry in self.registry.iter() {
+            entry.addr.send(broadcast_msg.get_message());
         }
     }
 
@@ -208,56 +1040,116 @@ impl ActorSystem {
     pub fn broadcast_ask<M: Send + Any + Clone>(&self, msg: M, reply_to: Addr) {
         let broadcast_msg = BroadcastMessage::with_sender(msg, reply_to);
 
-        for addr in self.registry.iter_mut() {
-            addr.send(broadcast_msg.get_message());
+        for entry in self.registry.iter() {
+            entry.addr.send(broadcast_msg.get_message());
+        }
+    }
+
+    /// Sends given message to all [Actor]'s with the given tag which are run on this [ActorSystem]
+    /// without specifying a reply_to [Addr](crate::address::Addr).
+    pub fn broadcast_tell_to<M: Send + Any + Clone>(&self, tag: &str, msg: M) {
+        let broadcast_msg = BroadcastMessage::without_sender(msg);
+
+        for entry in self.registry.iter().filter(|entry| entry.has_tag(tag)) {
+            entry.addr.send(broadcast_msg.get_message());
         }
     }
 
+    /// Sends given message to all [Actor]'s with the given tag which are run on this [ActorSystem]
+    /// with a given reply_to [Addr](crate::address::Addr).
+    pub fn broadcast_ask_to<M: Send + Any + Clone>(&self, tag: &str, msg: M, reply_to: Addr) {
+        let broadcast_msg = BroadcastMessage::with_sender(msg, reply_to);
+
+        for entry in self.registry.iter().filter(|entry| entry.has_tag(tag)) {
+            entry.addr.send(broadcast_msg.get_message());
+        }
+    }
+
+    /// Returns the [Addr](crate::address::Addr)'s of all [Actor]'s with the given tag which are run
+    /// on this [ActorSystem].
+    pub fn query_by_tag(&self, tag: &str) -> Vec<Addr> {
+        self.registry.iter()
+            .filter(|entry| entry.has_tag(tag))
+            .map(|entry| entry.addr.clone())
+            .collect()
+    }
+
+    /// Returns the number of [Actor]'s with the given tag which are run on this [ActorSystem].
+    pub fn count_by_tag(&self, tag: &str) -> usize {
+        self.registry.iter().filter(|entry| entry.has_tag(tag)).count()
+    }
+
+    /// Publishes the given message on the given topic. The message is sent to all [Actor]'s which
+    /// subscribed to messages of type M on this topic using [ActorContext.subscribe()](crate::actor::ActorContext#method.subscribe)
+    /// and to all [Actor]'s which subscribed to all messages of type M using
+    /// [ActorContext.subscribe_type()](crate::actor::ActorContext#method.subscribe_type). No reply_to [Addr](crate::address::Addr) is specified.
+    pub fn publish<M: Send + Any + Clone>(&self, topic: &str, msg: M) {
+        self.event_bus.publish(topic, msg);
+    }
+
+    /// Reports the given message, which has been dropped because the bounded mailbox of the given
+    /// recipient is full, as [DeadLetter].
+    pub(crate) fn report_full_mailbox(&self, recipient: &Addr, msg: &Message) {
+        let name = self.registry.iter()
+            .find(|entry| entry.addr.is_same(recipient))
+            .map(|entry| entry.key().clone())
+            .unwrap_or("anonymous".to_string());
+        warn!("Dropping message of type {} since the mailbox of actor {} is full", msg.type_name(), name);
+        #[cfg(feature = "metrics")]
+        self.metrics.record_dead_letters(1);
+        // dead letters are not reported for dead letters, such that full subscribers do not loop
+        if !msg.is::<DeadLetter>() {
+            self.publish(DEAD_LETTERS_TOPIC, DeadLetter {
+                recipient: name,
+                message_type: msg.type_name(),
+                is_ask: msg.is_ask()
+            });
+        }
+    }
+
+    pub(crate) fn event_bus(&self) -> &EventBus {
+        &self.event_bus
+    }
+
     /// Spawns a given [TestActor] without a [SupervisionStrategy]. This function is used
-    /// to test Actors with the testing framework and returns True for a successful test and false
-    /// for a not successful test. Note that the result has to be await-ed in the test function.
-    pub async fn spawn_test<S: Send>(self: &Arc<Self>, mut actor:  Actor<TestActor<S>>) -> bool {
+    /// to test Actors with the testing framework and returns a [TestReport] with the log of all
+    /// executed steps for a successful test. For a not successful test a [TestFailure] is returned,
+    /// which names the failed task, the reason and the expected and received message types. Note
+    /// that the result has to be await-ed in the test function.
+    pub async fn spawn_test<S: Send>(self: &Arc<Self>, mut actor:  Actor<TestActor<S>>) -> Result<TestReport, TestFailure> {
 
-        let name = "test_actor".to_string();
+        // unique name such that multiple tests can run on the same actor system at once
+        let name = format!("$test-{}", actor.get_id());
         let name_backup = name.clone();
 
         // set reference in actor to actor_system
         actor.set_actor_sys(self.clone());
-        self.registry.insert(name, actor.get_addr());
+        actor.set_name(&name);
+        self.register(name, RegistryEntry::for_actor(&actor, false));
+        self.schedule_actor(actor.get_id(), actor.get_addr(), true);
+        // shared with the test actor, such that its progress is known even if it panics
+        let progress = actor.state().progress();
 
-        // Arc handle for passing on into future for removing actor from registry before killing actor
-        let sys_ref = self.clone();
-
-        let test_result = tokio::spawn(async move {
+        let task = tokio::spawn(async move {
             let actor_exit_reason = actor.run().await;
+            actor.state().result(actor_exit_reason)
+        });
+        self.track(&task);
+        let test_result = task.await;
+        // the test actor is removed regardless of how it has exited, including panics
+        self.unregister(&name_backup);
 
-            match actor_exit_reason {
-                ExitReason::Kill => {
-                    info!("ActorTest {} passed successfully", &name_backup);
-                    // remove actor from registry before exiting run loop
-                    sys_ref.registry.remove(&name_backup);
-                    return true;
-                }
-                ExitReason::Restart => {
-                    return false;
-                }
-                ExitReason::Error => {
-                    info!("ActorTest {} failed with error.", &name_backup);
-                    // remove actor from registry before exiting run loop
-                    sys_ref.registry.remove(&name_backup);
-                    return false;
-                }
-            }
-        }).await;
-
-        match test_result {
-            Ok(res) => {
-                return res;
+        // the test actor panicked, e.g. in a criteria function
+        let res = test_result.unwrap_or_else(|_| Err(progress.lock().unwrap().aborted()));
+        match &res {
+            Ok(_) => {
+                info!("ActorTest {} passed successfully", &name_backup);
             }
-            Err(_) => {
-                return false;
+            Err(failure) => {
+                info!("ActorTest {} failed at {}: {}", &name_backup, failure.task, failure.reason);
             }
         }
+        res
     }
 }
 
@@ -1,29 +1,80 @@
 use std::any::Any;
+use std::marker::PhantomData;
+use std::sync::{Arc, OnceLock, Weak};
 use std::time::Duration;
 
 use tokio::sync::mpsc::{Sender, UnboundedSender};
-use tokio::time::sleep;
+use tokio::sync::mpsc::error::TrySendError;
 
-use crate::message::Message;
+use crate::actor::{ActorId, MailboxMetrics, MailboxStats, PriorityQueue};
+use crate::actor_system::ActorSystem;
+use crate::clock::spawn_delayed;
+use crate::message::{Envelope, Message};
 
 #[derive(Clone)]
 enum SenderType {
     Unbounded(UnboundedSender<Message>),
-    Bounded(Sender<Message>)
+    Bounded(Sender<Message>),
+    Priority(Arc<PriorityQueue>)
 }
 
 impl SenderType {
-    pub(crate) fn send(&self, msg: Message) {
+    /// Sends the given message. If a bounded mailbox is full, the message is sent once there is
+    /// capacity again if wait_if_full returns true for it and dropped otherwise.
+    pub(crate) fn send<F: FnOnce(&Message) -> bool>(&self, mut msg: Message, stats: Arc<MailboxStats>, wait_if_full: F) {
+        msg.mark_enqueued();
+        // the counter is incremented before sending such that the receiver never decrements it below zero
+        stats.enqueued();
         match self {
             SenderType::Unbounded(tx) => {
-                tx.send(msg);
+                if tx.send(msg).is_err() {
+                    stats.dequeued();
+                } else {
+                    stats.received();
+                }
             }
             SenderType::Bounded(tx) => {
+                // only wait for capacity in a separate task if the mailbox is full, such that
+                // messages are enqueued in the order they are sent whenever possible
+                let msg = match tx.try_send(msg) {
+                    Ok(()) => {
+                        stats.received();
+                        return;
+                    }
+                    Err(TrySendError::Closed(_)) => {
+                        stats.dequeued();
+                        return;
+                    }
+                    Err(TrySendError::Full(msg)) if !wait_if_full(&msg) => {
+                        stats.dequeued();
+                        return;
+                    }
+                    Err(TrySendError::Full(msg)) => msg
+                };
                 let tx = tx.clone();
                 tokio::spawn(async move {
-                    tx.send(msg).await;
+                    if tx.send(msg).await.is_err() {
+                        stats.dequeued();
+                    } else {
+                        stats.received();
+                    }
                 });
             }
+            SenderType::Priority(queue) => {
+                if !queue.push(msg) {
+                    stats.dequeued();
+                } else {
+                    stats.received();
+                }
+            }
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        match self {
+            SenderType::Unbounded(tx) => tx.is_closed(),
+            SenderType::Bounded(tx) => tx.is_closed(),
+            SenderType::Priority(queue) => queue.is_closed()
         }
     }
 }
@@ -33,33 +84,135 @@ impl SenderType {
 /// has exactly one [Addr] through which other [Actor](crate::actor::Actor)'s can communicate with
 /// it.
 pub struct Addr {
-    tx: SenderType
+    tx: SenderType,
+    /// Control lane of the mailbox for system messages, see [Message.is_system()](Message#method.is_system).
+    control: UnboundedSender<Message>,
+    /// Counters of the mailbox, shared with the mailbox and the actor behind it.
+    stats: Arc<MailboxStats>,
+    /// Actor system the actor behind this address has been spawned on, shared by all clones.
+    system: Arc<OnceLock<Weak<ActorSystem>>>
 }
 
 impl Addr {
-    pub(crate) fn unbounded(tx: UnboundedSender<Message>) -> Self {
+    pub(crate) fn unbounded(tx: UnboundedSender<Message>, control: UnboundedSender<Message>, stats: Arc<MailboxStats>) -> Self {
         Self {
-            tx: SenderType::Unbounded(tx)
+            tx: SenderType::Unbounded(tx),
+            control,
+            stats,
+            system: Arc::new(OnceLock::new())
         }
     }
 
-    pub(crate) fn bounded(tx: Sender<Message>) -> Self {
+    pub(crate) fn bounded(tx: Sender<Message>, control: UnboundedSender<Message>, stats: Arc<MailboxStats>) -> Self {
         Self {
-            tx: SenderType::Bounded(tx)
+            tx: SenderType::Bounded(tx),
+            control,
+            stats,
+            system: Arc::new(OnceLock::new())
         }
     }
 
+    pub(crate) fn priority(queue: Arc<PriorityQueue>, control: UnboundedSender<Message>, stats: Arc<MailboxStats>) -> Self {
+        Self {
+            tx: SenderType::Priority(queue),
+            control,
+            stats,
+            system: Arc::new(OnceLock::new())
+        }
+    }
+
+    /// Sends the given message to the mailbox behind this address. System messages are sent through
+    /// the control lane, such that they are handled before any pending data messages. Messages sent
+    /// while handling a replayed message are dropped, since they are part of the recording themselves.
     pub(crate) fn send(&self, msg: Message) {
-        self.tx.send(msg);
+        if Envelope::current_is_replayed() {
+            return;
+        }
+        if msg.is_system() {
+            self.send_control(msg);
+        } else {
+            self.tx.send(msg, self.stats.clone(), |msg| self.wait_if_full(msg));
+        }
     }
 
-    fn send_with_delay(&self, msg: Message, delay: Duration) {
-        let tx = self.tx.clone();
+    /// Sends the given message through the data lane of the mailbox behind this address, even if it
+    /// is a system message, such that it is handled after all messages which are already pending.
+    pub(crate) fn send_data(&self, msg: Message) {
+        self.tx.send(msg, self.stats.clone(), |msg| self.wait_if_full(msg));
+    }
+
+    /// Sends the given message through the control lane of the mailbox behind this address.
+    pub(crate) fn send_control(&self, mut msg: Message) {
+        msg.mark_enqueued();
+        self.stats.control_enqueued();
+        // the control lane is unbounded, it is only closed once the actor has exited
+        if self.control.send(msg).is_ok() {
+            self.stats.received();
+        } else {
+            self.stats.control_dequeued();
+        }
+    }
 
-        tokio::spawn(async move {
-            sleep(delay).await;
-            tx.send(msg);
-        });
+    /// Returns a snapshot of the load of the mailbox behind this address.
+    pub fn metrics(&self) -> MailboxMetrics {
+        self.stats.metrics()
+    }
+
+    /// Returns the counters of the mailbox behind this address.
+    pub(crate) fn stats(&self) -> &MailboxStats {
+        &self.stats
+    }
+
+    /// Returns the number of messages which are currently waiting in the mailbox.
+    pub(crate) fn mailbox_len(&self) -> usize {
+        self.stats.queued()
+    }
+
+    /// Returns true if the mailbox behind this address does not exist anymore, i.e. the actor has exited.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Binds this address and all its clones to the actor system the actor behind it is spawned on.
+    pub(crate) fn bind_system(&self, sys: &Arc<ActorSystem>) {
+        let _ = self.system.set(Arc::downgrade(sys));
+    }
+
+    /// Returns the actor system the actor behind this address has been spawned on.
+    fn system(&self) -> Option<Arc<ActorSystem>> {
+        self.system.get().and_then(|sys| sys.upgrade())
+    }
+
+    /// Returns true if the given message is to be sent once the full bounded mailbox behind this
+    /// address has capacity again. On deterministic actor systems the message is dropped and
+    /// reported as [DeadLetter](crate::actor_system::DeadLetter) instead, since the order in which
+    /// waiting messages get capacity is not deterministic.
+    fn wait_if_full(&self, msg: &Message) -> bool {
+        match self.system() {
+            Some(sys) if sys.scheduler().is_some() => {
+                sys.report_full_mailbox(self, msg);
+                false
+            }
+            _ => true
+        }
+    }
+
+    /// Returns true if both addresses point to the same mailbox.
+    pub(crate) fn is_same(&self, other: &Addr) -> bool {
+        Arc::ptr_eq(&self.stats, &other.stats)
+    }
+
+    fn send_with_delay(&self, msg: Message, delay: Duration) {
+        if Envelope::current_is_replayed() {
+            return;
+        }
+        let addr = self.clone();
+        let send = Box::new(move || addr.send(msg));
+        // delays are measured by the clock of the actor system the actor is spawned on
+        match self.system() {
+            Some(sys) => sys.clock().schedule(delay, send),
+            None => spawn_delayed(delay, send)
+        }
     }
 
     /// Sends the given message to the [Actor](crate::actor::Actor) behind this [Addr] without
@@ -94,7 +247,218 @@ impl Addr {
 impl Clone for Addr {
     fn clone(&self) -> Self {
         Addr {
-            tx: self.tx.clone()
+            tx: self.tx.clone(),
+            control: self.control.clone(),
+            stats: self.stats.clone(),
+            system: self.system.clone()
         }
     }
 }
+
+/// Represents the address of an [Actor](crate::actor::Actor) whose behavior handles messages of the
+/// protocol type P, which is usually an enum of all messages the [Actor](crate::actor::Actor) accepts.
+/// In contrast to [Addr] only messages which can be converted into P can be sent, such that
+/// sending a message the [Actor](crate::actor::Actor) cannot handle fails to compile.
+/// A [TypedAddr] can be obtained with [Actor.get_typed_addr()](crate::actor::Actor#method.get_typed_addr),
+/// which guarantees that the behavior of the actor handles P both with tell and ask at that time,
+/// and converted back into an [Addr] for dynamic use at any time. See [TellAddr] and [AskAddr] for
+/// actors which only handle one of both.
+pub struct TypedAddr<P: Any + Send> {
+    addr: Addr,
+    protocol: PhantomData<fn(P)>
+}
+
+impl<P: Any + Send> TypedAddr<P> {
+    pub(crate) fn new(addr: Addr) -> Self {
+        Self {
+            addr,
+            protocol: PhantomData
+        }
+    }
+
+    /// Sends the given message to the [Actor](crate::actor::Actor) behind this [TypedAddr] without
+    /// specifying a reply_to address.
+    pub fn tell<M: Into<P>>(&self, msg: M) {
+        self.addr.tell::<P>(msg.into());
+    }
+
+    /// Sends the given message to the [Actor](crate::actor::Actor) behind this [TypedAddr] with
+    /// a reply_to address.
+    pub fn ask<M: Into<P>>(&self, msg: M, reply_to: Addr) {
+        self.addr.ask::<P>(msg.into(), reply_to);
+    }
+
+    /// Sends the given message to the [Actor](crate::actor::Actor) behind this [TypedAddr] after a
+    /// specified delay without specifying a reply_to address.
+    pub fn tell_delayed<M: Into<P>>(&self, msg: M, delay: Duration) {
+        self.addr.tell_delayed::<P>(msg.into(), delay);
+    }
+
+    /// Sends the given message to the [Actor](crate::actor::Actor) behind this [TypedAddr] after a
+    /// specified delay with a reply_to address.
+    pub fn ask_delayed<M: Into<P>>(&self, msg: M, reply_to: Addr, delay: Duration) {
+        self.addr.ask_delayed::<P>(msg.into(), reply_to, delay);
+    }
+
+    /// Returns the untyped [Addr] of the [Actor](crate::actor::Actor) behind this [TypedAddr].
+    pub fn get_addr(&self) -> Addr {
+        self.addr.clone()
+    }
+}
+
+impl<P: Any + Send> Clone for TypedAddr<P> {
+    fn clone(&self) -> Self {
+        TypedAddr::new(self.addr.clone())
+    }
+}
+
+impl<P: Any + Send> From<TypedAddr<P>> for Addr {
+    fn from(typed_addr: TypedAddr<P>) -> Self {
+        typed_addr.addr
+    }
+}
+
+/// Address of an [Actor](crate::actor::Actor) whose behavior handles messages of the protocol type
+/// P sent with tell. Like [TypedAddr], but only allows sending messages with tell. Can be obtained
+/// with [Actor.get_tell_addr()](crate::actor::Actor#method.get_tell_addr).
+pub struct TellAddr<P: Any + Send> {
+    addr: Addr,
+    protocol: PhantomData<fn(P)>
+}
+
+impl<P: Any + Send> TellAddr<P> {
+    pub(crate) fn new(addr: Addr) -> Self {
+        Self {
+            addr,
+            protocol: PhantomData
+        }
+    }
+
+    /// Sends the given message to the [Actor](crate::actor::Actor) behind this [TellAddr] without
+    /// specifying a reply_to address.
+    pub fn tell<M: Into<P>>(&self, msg: M) {
+        self.addr.tell::<P>(msg.into());
+    }
+
+    /// Sends the given message to the [Actor](crate::actor::Actor) behind this [TellAddr] after a
+    /// specified delay without specifying a reply_to address.
+    pub fn tell_delayed<M: Into<P>>(&self, msg: M, delay: Duration) {
+        self.addr.tell_delayed::<P>(msg.into(), delay);
+    }
+
+    /// Returns the untyped [Addr] of the [Actor](crate::actor::Actor) behind this [TellAddr].
+    pub fn get_addr(&self) -> Addr {
+        self.addr.clone()
+    }
+}
+
+impl<P: Any + Send> Clone for TellAddr<P> {
+    fn clone(&self) -> Self {
+        TellAddr::new(self.addr.clone())
+    }
+}
+
+impl<P: Any + Send> From<TellAddr<P>> for Addr {
+    fn from(tell_addr: TellAddr<P>) -> Self {
+        tell_addr.addr
+    }
+}
+
+impl<P: Any + Send> From<TypedAddr<P>> for TellAddr<P> {
+    fn from(typed_addr: TypedAddr<P>) -> Self {
+        TellAddr::new(typed_addr.addr)
+    }
+}
+
+/// Address of an [Actor](crate::actor::Actor) whose behavior handles messages of the protocol type
+/// P sent with ask. Like [TypedAddr], but only allows sending messages with ask. Can be obtained
+/// with [Actor.get_ask_addr()](crate::actor::Actor#method.get_ask_addr).
+pub struct AskAddr<P: Any + Send> {
+    addr: Addr,
+    protocol: PhantomData<fn(P)>
+}
+
+impl<P: Any + Send> AskAddr<P> {
+    pub(crate) fn new(addr: Addr) -> Self {
+        Self {
+            addr,
+            protocol: PhantomData
+        }
+    }
+
+    /// Sends the given message to the [Actor](crate::actor::Actor) behind this [AskAddr] with
+    /// a reply_to address.
+    pub fn ask<M: Into<P>>(&self, msg: M, reply_to: Addr) {
+        self.addr.ask::<P>(msg.into(), reply_to);
+    }
+
+    /// Sends the given message to the [Actor](crate::actor::Actor) behind this [AskAddr] after a
+    /// specified delay with a reply_to address.
+    pub fn ask_delayed<M: Into<P>>(&self, msg: M, reply_to: Addr, delay: Duration) {
+        self.addr.ask_delayed::<P>(msg.into(), reply_to, delay);
+    }
+
+    /// Returns the untyped [Addr] of the [Actor](crate::actor::Actor) behind this [AskAddr].
+    pub fn get_addr(&self) -> Addr {
+        self.addr.clone()
+    }
+}
+
+impl<P: Any + Send> Clone for AskAddr<P> {
+    fn clone(&self) -> Self {
+        AskAddr::new(self.addr.clone())
+    }
+}
+
+impl<P: Any + Send> From<AskAddr<P>> for Addr {
+    fn from(ask_addr: AskAddr<P>) -> Self {
+        ask_addr.addr
+    }
+}
+
+impl<P: Any + Send> From<TypedAddr<P>> for AskAddr<P> {
+    fn from(typed_addr: TypedAddr<P>) -> Self {
+        AskAddr::new(typed_addr.addr)
+    }
+}
+
+/// Reference to an [Actor](crate::actor::Actor) which has been spawned on an
+/// [ActorSystem](crate::actor_system::ActorSystem). Contains the name under which the actor is
+/// registered, its unique [ActorId] and its [Addr].
+#[derive(Clone)]
+pub struct ActorRef {
+    name: String,
+    id: ActorId,
+    addr: Addr
+}
+
+impl ActorRef {
+    pub(crate) fn new(name: String, id: ActorId, addr: Addr) -> Self {
+        Self {
+            name,
+            id,
+            addr
+        }
+    }
+
+    /// Returns the name under which the actor is registered.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the unique id of the actor.
+    pub fn id(&self) -> ActorId {
+        self.id
+    }
+
+    /// Returns the [Addr] of the actor.
+    pub fn get_addr(&self) -> Addr {
+        self.addr.clone()
+    }
+}
+
+impl From<ActorRef> for Addr {
+    fn from(actor_ref: ActorRef) -> Self {
+        actor_ref.addr
+    }
+}
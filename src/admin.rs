@@ -0,0 +1,270 @@
+//! Admin console of an [ActorSystem] which listens on a local Unix domain socket and accepts line
+//! oriented commands, such that operators can inspect and manage a running actor system. This module
+//! is only available with the `admin` feature on Unix platforms. The `aector-admin` binary can be used
+//! to connect to the console.
+//!
+//! Supported commands:
+//! - `list`: lists all registered actors
+//! - `stats <name>`: shows the mailbox load, restarts and uptime of an actor
+//! - `kill <name>`: kills an actor
+//! - `restart <name>`: restarts an actor
+//! - `tell <name> <json>`: sends a message to an actor. The message is given as JSON object with a
+//!   single key naming a message type registered with [AdminConsole.register()](AdminConsole#method.register),
+//!   e.g. `tell account {"Deposit": {"amount": 10}}`
+//! - `tail dead-letters`: streams all [DeadLetter]'s until the connection is closed
+//!
+//! The response to every command except `tail` ends with a line which is either `ok` or starts with `error:`.
+//!
+//! Example:
+//! ```no_run
+//! use aector::actor_system::ActorSystem;
+//! use aector::admin::AdminConsole;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Deposit {
+//!     amount: u32
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let actor_sys = ActorSystem::new();
+//! let console = AdminConsole::new("/tmp/my-service.sock")
+//!     .register::<Deposit>("Deposit");
+//! actor_sys.start_admin(console).unwrap();
+//! # }
+//! ```
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::net::unix::OwnedWriteHalf;
+use tracing::{error, info};
+
+use crate::actor::{ActorId, Mailbox};
+use crate::actor_system::{ActorSystem, DeadLetter, DEAD_LETTERS_TOPIC};
+use crate::address::Addr;
+use crate::behavior::ActorManageMessage;
+
+/// Decodes a message from JSON and sends it to the given address.
+type TellFn = fn(Value, &Addr) -> serde_json::Result<()>;
+
+fn decode_and_tell<M: DeserializeOwned + Any + Send>(value: Value, addr: &Addr) -> serde_json::Result<()> {
+    let msg: M = serde_json::from_value(value)?;
+    addr.tell(msg);
+    Ok(())
+}
+
+/// Configuration of the admin console of an [ActorSystem], started with
+/// [ActorSystem.start_admin()](crate::actor_system::ActorSystem#method.start_admin).
+#[derive(Clone)]
+pub struct AdminConsole {
+    path: PathBuf,
+    messages: HashMap<String, TellFn>
+}
+
+impl AdminConsole {
+    /// Creates an admin console which listens on the Unix socket at the given path. An existing
+    /// socket at this path is replaced.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            messages: HashMap::new()
+        }
+    }
+
+    /// Registers the message type M under the given name, such that messages of this type can be
+    /// sent to actors with the `tell` command.
+    pub fn register<M: DeserializeOwned + Any + Send>(mut self, name: &str) -> Self {
+        self.messages.insert(name.to_string(), decode_and_tell::<M>);
+        self
+    }
+
+    /// Returns the path of the socket the console listens on.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Binds the socket of the given console. Stale sockets, e.g. of a previous run which has not been
+/// shut down properly, are removed, other files are never overwritten.
+pub(crate) fn bind(console: &AdminConsole) -> std::io::Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(&console.path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(&console.path)?;
+        }
+    }
+    UnixListener::bind(&console.path)
+}
+
+/// Accepts connections to the console until the actor system is stopped. The socket is removed afterwards.
+pub(crate) async fn serve(sys: Arc<ActorSystem>, console: AdminConsole, listener: UnixListener) {
+    let console = Arc::new(console);
+    let mut shutdown = sys.subscribe_shutdown();
+    loop {
+        tokio::select! {
+            _ = shutdown.wait_for(|stopped| *stopped) => {
+                info!("Actor system stopped. Closing admin console");
+                let _ = std::fs::remove_file(&console.path);
+                return;
+            }
+            conn = listener.accept() => {
+                match conn {
+                    Ok((stream, _)) => {
+                        let sys = sys.clone();
+                        let console = console.clone();
+                        tokio::spawn(async move {
+                            if let Err(err) = handle_connection(sys, console, stream).await {
+                                error!("Admin connection failed: {}", err);
+                            }
+                        });
+                    }
+                    Err(err) => {
+                        error!("Failed to accept admin connection: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Answers the commands sent over the given connection until it is closed.
+async fn handle_connection(sys: Arc<ActorSystem>, console: Arc<AdminConsole>, stream: UnixStream) -> std::io::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+
+        if command == "tail" {
+            if args != "dead-letters" {
+                write.write_all(format!("error: cannot tail {}\n", args).as_bytes()).await?;
+                continue;
+            }
+            // the connection is used for streaming from now on, it ends once the client disconnects
+            return tail_dead_letters(&sys, &mut write, lines).await;
+        }
+
+        let response = match execute(&sys, &console, command, args) {
+            Ok(output) => format!("{}ok\n", output),
+            Err(err) => format!("error: {}\n", err)
+        };
+        write.write_all(response.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Executes a single command and returns its output, each line terminated by a line break.
+fn execute(sys: &Arc<ActorSystem>, console: &AdminConsole, command: &str, args: &str) -> Result<String, String> {
+    match command {
+        "list" => {
+            let mut out = String::new();
+            for actor in sys.dump().actors {
+                let _ = write!(out, "{} id={} behavior={} mailbox={} restarts={} uptime={}s",
+                    actor.name, actor.id, actor.behavior.as_deref().unwrap_or("router"),
+                    actor.mailbox_depth, actor.restarts, actor.uptime.as_secs());
+                if let Some(parent) = &actor.parent {
+                    let _ = write!(out, " parent={}", parent);
+                }
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        "stats" => {
+            let actor = sys.dump().get(args).cloned().ok_or_else(|| format!("no actor named {}", args))?;
+            let addr = sys.query(args).ok_or_else(|| format!("no actor named {}", args))?;
+            let metrics = addr.metrics();
+            let mut out = String::new();
+            let _ = writeln!(out, "name: {}", actor.name);
+            let _ = writeln!(out, "id: {}", actor.id);
+            let _ = writeln!(out, "behavior: {}", actor.behavior.as_deref().unwrap_or("router"));
+            let _ = writeln!(out, "parent: {}", actor.parent.as_deref().unwrap_or("-"));
+            let _ = writeln!(out, "children: {}", actor.children.join(", "));
+            let _ = writeln!(out, "mailbox: {}/{}", metrics.len, metrics.capacity.map(|c| c.to_string()).unwrap_or("unbounded".to_string()));
+            let _ = writeln!(out, "received: {}", metrics.received);
+            let _ = writeln!(out, "handled: {}", metrics.handled);
+            let last_handled = metrics.last_handled
+                .and_then(|at| SystemTime::now().duration_since(at).ok())
+                .map(|ago| format!("{}ms ago", ago.as_millis()))
+                .unwrap_or("never".to_string());
+            let _ = writeln!(out, "last handled: {}", last_handled);
+            let _ = writeln!(out, "restarts: {}", actor.restarts);
+            let _ = writeln!(out, "uptime: {}s", actor.uptime.as_secs());
+            Ok(out)
+        }
+        "kill" => {
+            let addr = sys.query(args).ok_or_else(|| format!("no actor named {}", args))?;
+            addr.tell(ActorManageMessage::Kill);
+            Ok(String::new())
+        }
+        "restart" => {
+            let addr = sys.query(args).ok_or_else(|| format!("no actor named {}", args))?;
+            addr.tell(ActorManageMessage::Restart);
+            Ok(String::new())
+        }
+        "tell" => {
+            let (name, json) = args.split_once(' ').ok_or("usage: tell <name> <json>")?;
+            let addr = sys.query(name).ok_or_else(|| format!("no actor named {}", name))?;
+            let value: Value = serde_json::from_str(json.trim()).map_err(|err| format!("invalid json: {}", err))?;
+            let (message_type, content) = match value {
+                Value::Object(object) if object.len() == 1 => object.into_iter().next().unwrap(),
+                _ => return Err("message must be a json object with the message type as its only key".to_string())
+            };
+            let tell = console.messages.get(&message_type).ok_or_else(|| format!("unknown message type {}", message_type))?;
+            tell(content, &addr).map_err(|err| format!("invalid {}: {}", message_type, err))?;
+            Ok(String::new())
+        }
+        _ => {
+            Err(format!("unknown command {}", command))
+        }
+    }
+}
+
+/// Writes every published [DeadLetter] to the connection until the client disconnects.
+async fn tail_dead_letters<R>(sys: &ActorSystem, write: &mut OwnedWriteHalf, mut lines: tokio::io::Lines<R>) -> std::io::Result<()>
+where
+    R: tokio::io::AsyncBufRead + Unpin
+{
+    let mut mailbox = Mailbox::unbounded();
+    // the connection subscribes like an actor with its own id
+    let id = ActorId::next();
+    sys.event_bus().subscribe::<DeadLetter>(DEAD_LETTERS_TOPIC, id, mailbox.get_addr());
+
+    let res = async {
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    // further commands are ignored, the stream ends once the client disconnects
+                    if line?.is_none() {
+                        return Ok(());
+                    }
+                }
+                msg = mailbox.recv() => {
+                    let letter = match msg {
+                        Some(msg) if msg.is::<DeadLetter>() => msg.downcast::<DeadLetter>(),
+                        _ => continue
+                    };
+                    let kind = if letter.is_ask { "ask" } else { "tell" };
+                    write.write_all(format!("{} {} {}\n", letter.recipient, kind, letter.message_type).as_bytes()).await?;
+                }
+            }
+        }
+    }.await;
+
+    sys.event_bus().unsubscribe::<DeadLetter>(DEAD_LETTERS_TOPIC, id);
+    res
+}
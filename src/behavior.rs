@@ -3,14 +3,22 @@
 
 use std::any::{Any, TypeId};
 use std::collections::{HashMap};
+use std::collections::hash_map::Entry;
+use std::fmt::{self, Debug};
 use std::error::Error;
 use std::panic;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use thiserror::Error;
 
 use crate::actor::ActorContext;
 use crate::address::Addr;
-use crate::message::Message;
+use crate::message::{Message, MessageMeta};
 
+/// Kills or restarts the receiving actor. Manage messages are sent through the control lane of the
+/// mailbox and are thus handled before any pending data messages.
+#[derive(Clone, Copy)]
 pub enum ActorManageMessage {
     Kill,
     Restart
@@ -30,22 +38,110 @@ type UserDefinedAskHandlerFn<M: Any + Send, S: Send + 'static> = fn(M, &mut S, A
 type UserDefinedTellHandlerFn<M: Any + Send, S: Send + 'static> = fn(M, &mut S, &mut ActorContext) -> BehaviorAction<S>;
 
 /// Type of closures which are run by the actor without any message such as on_start, on_error, ..
-type PlainActorAction<S: Send + 'static> = fn(&mut S, &mut ActorContext) -> ();
+/// Arc is used for the same reason as in [HandlerFn].
+type PlainActorAction<S: Send + 'static> = Arc<dyn Fn(&mut S, &mut ActorContext) + Send + Sync>;
 
 /// Message handler as defined by user when working with BehaviorBuilder for tell (i.e. without passing Addr of sender of message), but with taking a closure instead of a function pointer
 type UserDefinedTellHandlerClosure<M: Any + Send, S: Send + 'static> = Box<dyn Fn(M, &mut S, &mut ActorContext) -> BehaviorAction<S> + Send + Sync>;
 /// Message handler as defined by user when working with BehaviorBuilder for ask (i.e. with passing Addr of sender of message), but with taking a closure instead of a function pointer
 type UserDefinedAskHandlerClosure<M: Any + Send, S: Send + 'static> = Box<dyn Fn(M, &mut S, Addr, &mut ActorContext) -> BehaviorAction<S> + Send + Sync>;
 
+/// A message handler together with the name of the message type it handles. The name is only used
+/// for reporting conflicts when merging behaviors.
+pub(crate) struct Handler<S: Send + 'static> {
+    type_name: &'static str,
+    f: HandlerFn<S>
+}
+
+impl<S: Send + 'static> Handler<S> {
+    fn new<M: Any + Send>(f: HandlerFn<S>) -> Self {
+        Self {
+            type_name: std::any::type_name::<M>(),
+            f
+        }
+    }
+}
+
+impl<S: Send + 'static> Clone for Handler<S> {
+    fn clone(&self) -> Self {
+        Self {
+            type_name: self.type_name,
+            f: self.f.clone()
+        }
+    }
+}
+
+/// Describes how conflicts are resolved when merging two behaviors which both define a handler for
+/// the same message type or the same action (e.g. on_start).
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum ConflictRule {
+    /// Merging fails with a [BehaviorError].
+    Error,
+    /// The handler of the behavior on which merge is called is kept.
+    PreferLeft,
+    /// The handler of the behavior which is passed on to merge is kept.
+    PreferRight
+}
+
+#[derive(Error, Debug)]
+/// This enum represents different errors which can occur when merging behaviors.
+#[non_exhaustive]
+pub enum BehaviorError {
+    #[error("Ask handler for {0} has been defined on both behaviors!")]
+    DuplicateAskHandler(&'static str),
+    #[error("Tell handler for {0} has been defined on both behaviors!")]
+    DuplicateTellHandler(&'static str),
+    #[error("Action {0} has been defined on both behaviors!")]
+    DuplicateAction(&'static str)
+}
+
+/// Interceptors as stored internally. Arc is used for the same reason as in [HandlerFn].
+type InterceptorFn<S> = Arc<dyn Fn(&MessageMeta, &mut S, &mut ActorContext, Next<S>) -> BehaviorAction<S> + Send + Sync>;
+
+/// Represents the remaining part of the handler chain of a message which is passed on to an interceptor.
+/// Calling [Next.run()](Next#method.run) continues the dispatch of the message to the next interceptor or,
+/// if there are no interceptors left, to the actual message handler. Dropping it without calling
+/// run() short-circuits the dispatch and the message is not handled.
+pub struct Next<'a, S: Send + 'static> {
+    msg: Message,
+    meta: &'a MessageMeta,
+    handler: &'a Handler<S>,
+    interceptors: &'a [InterceptorFn<S>]
+}
+
+impl<'a, S: Send + 'static> Next<'a, S> {
+    /// Continues the dispatch of the message and returns the [BehaviorAction] of the handler chain.
+    pub fn run(self, state: &mut S, ctx: &mut ActorContext) -> BehaviorAction<S> {
+        match self.interceptors.split_first() {
+            Some((interceptor, rest)) => {
+                let next = Next {
+                    msg: self.msg,
+                    meta: self.meta,
+                    handler: self.handler,
+                    interceptors: rest
+                };
+                interceptor(self.meta, state, ctx, next)
+            }
+            None => {
+                (self.handler.f)(self.msg, state, ctx)
+            }
+        }
+    }
+}
+
 
 /// This struct is used to build a [Behavior].
 pub struct BehaviorBuilder<S: Send + 'static> {
-    on_ask_handler: HashMap<TypeId, HandlerFn<S>>,
-    on_tell_handler: HashMap<TypeId, HandlerFn<S>>,
+    on_ask_handler: HashMap<TypeId, Handler<S>>,
+    on_tell_handler: HashMap<TypeId, Handler<S>>,
     on_start: Option<PlainActorAction<S>>,
     on_kill: Option<PlainActorAction<S>>,
     on_error: Option<PlainActorAction<S>>,
     on_restart: Option<PlainActorAction<S>>,
+    on_stop: Option<PlainActorAction<S>>,
+    interceptors: Vec<InterceptorFn<S>>,
+    name: Option<String>
 }
 
 impl<S: Send + 'static> BehaviorBuilder<S> {
@@ -57,10 +153,20 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
             on_start: None,
             on_kill: None,
             on_error: None,
-            on_restart: None
+            on_restart: None,
+            on_stop: None,
+            interceptors: Vec::new(),
+            name: None
         }
     }
 
+    /// Sets a name for the behavior which is shown in place of its id when inspecting actors, see
+    /// [ActorSystem.dump()](crate::actor_system::ActorSystem#method.dump).
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
     /// Defines handler for messages of type M for which the Addr of the sender has been passed on to the receiver.
     /// Only one ask_handler per message type can be defined per actor.
     pub fn on_ask<M: Any + Send>(mut self, h: UserDefinedAskHandlerFn<M, S>) -> Self {
@@ -93,7 +199,7 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
             panic!("Ask handler for {} has already been defined on this behavior! Cannot define more than one ask handler per message type per actor!", std::any::type_name::<M>());
         } else {
             // store handler associated with type
-            self.on_ask_handler.insert(TypeId::of::<M>(), Arc::new(h_wrapper));
+            self.on_ask_handler.insert(TypeId::of::<M>(), Handler::new::<M>(Arc::new(h_wrapper)));
             self
         }
     }
@@ -121,7 +227,7 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
             panic!("Tell handler for {} has already been defined on this behavior! Cannot define more than one tell handler per message type per actor!", std::any::type_name::<M>());
         } else {
             // store handler associated with type
-            self.on_tell_handler.insert(TypeId::of::<M>(), Arc::new(h_wrapper));
+            self.on_tell_handler.insert(TypeId::of::<M>(), Handler::new::<M>(Arc::new(h_wrapper)));
             self
         }
     }
@@ -129,33 +235,42 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
     /// This function defines the action an actor executes on its startup. This function is also called
     /// when an actor is restarted either after requesting it using [ActorContext.restart()](crate::actor::ActorContext#method.restart)
     /// or because of a restart caused by a [SupervisionStrategy](crate::supervision::SupervisionStrategy).
-    pub fn on_start(mut self, action: PlainActorAction<S>) -> Self {
+    pub fn on_start<F>(mut self, action: F) -> Self
+    where
+        F: Fn(&mut S, &mut ActorContext) + Send + Sync + 'static
+    {
         if let Some(_) = self.on_start {
             panic!("Cannot define more than one on_start methods for same actor!");
         } else {
-            self.on_start = Some(action);
+            self.on_start = Some(Arc::new(action));
             self
         }
     }
 
     /// This function defines the action an actor executes when it is killed by calling the
     /// [ActorContext.kill()](crate::actor::ActorContext#method.kill) function.
-    pub fn on_kill(mut self, action: PlainActorAction<S>) -> Self {
+    pub fn on_kill<F>(mut self, action: F) -> Self
+    where
+        F: Fn(&mut S, &mut ActorContext) + Send + Sync + 'static
+    {
         if let Some(_) = self.on_kill {
             panic!("Cannot define more than one on_kill methods for same actor!");
         } else {
-            self.on_kill = Some(action);
+            self.on_kill = Some(Arc::new(action));
             self
         }
     }
 
     /// This function defines the action an actor executes when it is killed cause of an error
     /// of any type occuring in an ask or tell handler.
-    pub fn on_error(mut self, action: PlainActorAction<S>) -> Self {
+    pub fn on_error<F>(mut self, action: F) -> Self
+    where
+        F: Fn(&mut S, &mut ActorContext) + Send + Sync + 'static
+    {
         if let Some(_) = self.on_error {
             panic!("Cannot define more than one on_error methods for same actor!");
         } else {
-            self.on_error = Some(action);
+            self.on_error = Some(Arc::new(action));
             self
         }
     }
@@ -163,15 +278,67 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
     /// This function defines the action an actor executes after a restart has been requested using [ActorContext.restart()](crate::actor::ActorContext#method.restart)
     /// before the actor is restarted. This function is also called if a a [SupervisionStrategy](crate::supervision::SupervisionStrategy)
     /// decies to restart an actor.
-    pub fn on_restart(mut self, action: PlainActorAction<S>) -> Self {
+    pub fn on_restart<F>(mut self, action: F) -> Self
+    where
+        F: Fn(&mut S, &mut ActorContext) + Send + Sync + 'static
+    {
         if let Some(_) = self.on_restart {
             panic!("Cannot define more than one on_restart methods for same actor!");
         } else {
-            self.on_restart = Some(action);
+            self.on_restart = Some(Arc::new(action));
+            self
+        }
+    }
+
+    /// This function defines the action an actor executes whenever it stops running, regardless of
+    /// whether it has been killed, exited because of an error or the [ActorSystem](crate::actor_system::ActorSystem)
+    /// has been stopped. It is run after the more specific actions such as on_kill or on_error.
+    pub fn on_stop<F>(mut self, action: F) -> Self
+    where
+        F: Fn(&mut S, &mut ActorContext) + Send + Sync + 'static
+    {
+        if self.on_stop.is_some() {
+            panic!("Cannot define more than one on_stop methods for same actor!");
+        } else {
+            self.on_stop = Some(Arc::new(action));
             self
         }
     }
 
+    /// Adds an interceptor which wraps the dispatch of every message for which a handler has been
+    /// defined on this behavior. The interceptor receives a description of the message, the state,
+    /// the context of the actor and the rest of the handler chain as [Next]. It can observe or replace
+    /// the [BehaviorAction] returned by [Next.run()](Next#method.run) or short-circuit the dispatch by not
+    /// calling it at all. Interceptors are run in the order in which they were added, i.e. the first
+    /// interceptor is the outermost one. Messages handled by the default handlers of the framework,
+    /// i.e. [ActorManageMessage]'s, state checks and state snapshot requests, are never intercepted.
+    pub fn intercept<F>(mut self, interceptor: F) -> Self
+    where
+        F: Fn(&MessageMeta, &mut S, &mut ActorContext, Next<S>) -> BehaviorAction<S> + Send + Sync + 'static
+    {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Merges the handlers, actions and interceptors of the given builder into this builder. If both
+    /// builders define a handler for the same message type or the same action, the conflict is
+    /// resolved according to the given [ConflictRule]. The interceptors of each builder keep wrapping
+    /// only the handlers of that builder, such that e.g. an interceptor of a partial behavior shipped
+    /// by a library does not apply to the handlers of the application. Interceptors which are added
+    /// after merging wrap all handlers.
+    pub fn merge(mut self, mut other: BehaviorBuilder<S>, rule: ConflictRule) -> Result<Self, BehaviorError> {
+        seal_interceptors(&mut self.on_ask_handler, &mut self.on_tell_handler, &mut self.interceptors);
+        seal_interceptors(&mut other.on_ask_handler, &mut other.on_tell_handler, &mut other.interceptors);
+        merge_handlers(&mut self.on_ask_handler, other.on_ask_handler, rule, BehaviorError::DuplicateAskHandler)?;
+        merge_handlers(&mut self.on_tell_handler, other.on_tell_handler, rule, BehaviorError::DuplicateTellHandler)?;
+        merge_action(&mut self.on_start, other.on_start, rule, "on_start")?;
+        merge_action(&mut self.on_kill, other.on_kill, rule, "on_kill")?;
+        merge_action(&mut self.on_error, other.on_error, rule, "on_error")?;
+        merge_action(&mut self.on_restart, other.on_restart, rule, "on_restart")?;
+        merge_action(&mut self.on_stop, other.on_stop, rule, "on_stop")?;
+        Ok(self)
+    }
+
     /// Enables the default handler for StateCheckMessage. This has to be called for all actors
     /// which are to be tested using the [crate::testing] module.
     pub fn enable_state_checks(self) -> Self {
@@ -179,7 +346,8 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
             match msg {
                 StateCheckMessage::Check(check_fn) => {
                     let res = check_fn(state);
-                    reply_to.tell(StateCheckMessage::<S>::Result(res));
+                    // the result is sent through the control lane, such that it is not stuck behind pending messages
+                    reply_to.send_control(Message::without_sender(StateCheckMessage::<S>::Result(res)));
                 }
                 _ => {}
             }
@@ -213,7 +381,7 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
             panic!("Tell handler for {} has already been defined on this behavior! Cannot define more than one tell handler per message type per actor!", std::any::type_name::<M>());
         } else {
             // store handler associated with type
-            self.on_tell_handler.insert(TypeId::of::<M>(), Arc::new(h_wrapper));
+            self.on_tell_handler.insert(TypeId::of::<M>(), Handler::new::<M>(Arc::new(h_wrapper)));
             self
         }
     }
@@ -249,7 +417,7 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
             panic!("Ask handler for {} has already been defined on this behavior! Cannot define more than one ask handler per message type per actor!", std::any::type_name::<M>());
         } else {
             // store handler associated with type
-            self.on_ask_handler.insert(TypeId::of::<M>(), Arc::new(h_wrapper));
+            self.on_ask_handler.insert(TypeId::of::<M>(), Handler::new::<M>(Arc::new(h_wrapper)));
             self
         }
     }
@@ -280,12 +448,16 @@ impl<S: Send + 'static> BehaviorBuilder<S> {
         });
 
         Behavior {
+            id: BehaviorId::next(),
+            name: b.name,
             on_ask_handler: b.on_ask_handler,
             on_tell_handler: b.on_tell_handler,
             on_start: b.on_start,
             on_kill: b.on_kill,
             on_error: b.on_error,
-            on_restart: b.on_restart
+            on_restart: b.on_restart,
+            on_stop: b.on_stop,
+            interceptors: b.interceptors
         }
     }
 }
@@ -295,18 +467,60 @@ pub enum StateCheckMessage<S> {
     Result(bool)
 }
 
+/// Requests the Debug representation of the state of an actor which has enabled state snapshots.
+pub(crate) struct StateSnapshotRequest;
+
+/// Debug representation of the state of an actor sent in reply to a [StateSnapshotRequest].
+pub(crate) struct StateSnapshot(pub(crate) String);
+
+impl<S: Send + Debug + 'static> BehaviorBuilder<S> {
+    /// Enables the default handler for snapshots of the state of the actor, which are used by
+    /// [ActorSystem.dump_with_states()](crate::actor_system::ActorSystem#method.dump_with_states)
+    /// to include the [Debug] representation of the state in the dump.
+    pub fn enable_state_snapshots(self) -> Self {
+        self.on_ask::<StateSnapshotRequest>(|_msg, state, reply_to, _ctx| -> BehaviorAction<S> {
+            reply_to.tell(StateSnapshot(format!("{:?}", state)));
+            Behavior::keep()
+        })
+    }
+}
+
+/// Source of the unique ids of behaviors.
+static NEXT_BEHAVIOR_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Unique id of a [Behavior]. Every call to [BehaviorBuilder.build()](BehaviorBuilder#method.build)
+/// creates a behavior with a new id, clones of a behavior share its id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BehaviorId(u64);
+
+impl BehaviorId {
+    fn next() -> Self {
+        BehaviorId(NEXT_BEHAVIOR_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for BehaviorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
 #[derive(Clone)]
 /// This struct defines the behavior of an actor. A behavior is defined by it's actions which
 /// are executed under special circumstances (e.g. on start, on error, etc.) but also how messages
 /// of different types and different requests (ask / tell) are handled. In order to build a [Behavior]
 /// see [BehaviorBuilder]
 pub struct Behavior<S: Send + 'static> {
-    pub(crate) on_ask_handler: HashMap<TypeId, HandlerFn<S>>,
-    pub(crate) on_tell_handler: HashMap<TypeId, HandlerFn<S>>,
+    id: BehaviorId,
+    name: Option<String>,
+    pub(crate) on_ask_handler: HashMap<TypeId, Handler<S>>,
+    pub(crate) on_tell_handler: HashMap<TypeId, Handler<S>>,
     pub(crate) on_start: Option<PlainActorAction<S>>,
     pub(crate) on_kill: Option<PlainActorAction<S>>,
     pub(crate) on_error: Option<PlainActorAction<S>>,
     pub(crate) on_restart: Option<PlainActorAction<S>>,
+    pub(crate) on_stop: Option<PlainActorAction<S>>,
+    interceptors: Vec<InterceptorFn<S>>,
 }
 
 impl<S: Send> Behavior<S> {
@@ -322,43 +536,195 @@ impl<S: Send> Behavior<S> {
     pub fn change(new_behavior: Behavior<S>) -> BehaviorAction<S> {
         Ok(Some(new_behavior))
     }
+
+    /// Returns the unique id of this behavior.
+    pub fn id(&self) -> BehaviorId {
+        self.id
+    }
+
+    /// Returns the name of this behavior if one has been set with [BehaviorBuilder.named()](BehaviorBuilder#method.named).
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the name of this behavior or its id if it is unnamed.
+    pub(crate) fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.id.to_string()
+        }
+    }
+
+    /// Returns true if this behavior answers state snapshot requests, see
+    /// [BehaviorBuilder.enable_state_snapshots()](BehaviorBuilder#method.enable_state_snapshots).
+    pub(crate) fn has_state_snapshots(&self) -> bool {
+        self.on_ask_handler.contains_key(&TypeId::of::<StateSnapshotRequest>())
+    }
+
+    /// Merges the handlers, actions and interceptors of the given behavior into this behavior. If both
+    /// behaviors define a handler for the same message type or the same action, the conflict is
+    /// resolved according to the given [ConflictRule]. The default handlers which are added to every
+    /// behavior (e.g. for [ActorManageMessage]) never conflict. The interceptors of each behavior keep
+    /// wrapping only the handlers of that behavior. The merged behavior keeps the id and name of this
+    /// behavior.
+    pub fn merge(mut self, mut other: Behavior<S>, rule: ConflictRule) -> Result<Behavior<S>, BehaviorError> {
+        seal_interceptors(&mut self.on_ask_handler, &mut self.on_tell_handler, &mut self.interceptors);
+        seal_interceptors(&mut other.on_ask_handler, &mut other.on_tell_handler, &mut other.interceptors);
+        merge_handlers(&mut self.on_ask_handler, other.on_ask_handler, rule, BehaviorError::DuplicateAskHandler)?;
+        merge_handlers(&mut self.on_tell_handler, other.on_tell_handler, rule, BehaviorError::DuplicateTellHandler)?;
+        merge_action(&mut self.on_start, other.on_start, rule, "on_start")?;
+        merge_action(&mut self.on_kill, other.on_kill, rule, "on_kill")?;
+        merge_action(&mut self.on_error, other.on_error, rule, "on_error")?;
+        merge_action(&mut self.on_restart, other.on_restart, rule, "on_restart")?;
+        merge_action(&mut self.on_stop, other.on_stop, rule, "on_stop")?;
+        Ok(self)
+    }
+
+    /// Returns a behavior which handles messages and runs actions using this behavior and falls
+    /// back to the given behavior for everything this behavior does not define.
+    pub fn or_else(self, other: Behavior<S>) -> Behavior<S> {
+        match self.merge(other, ConflictRule::PreferLeft) {
+            Ok(merged) => merged,
+            // PreferLeft never reports a conflict
+            Err(_) => unreachable!()
+        }
+    }
 }
 
-impl<S: Send + 'static> Behavior<S> {
-    pub(crate) fn handle(&mut self, msg: Message, state: &mut S, ctx: &mut ActorContext) -> BehaviorAction<S> {
-        // if message contains sender: assume on_ask handler, otherwise on_tell handler
-        match &msg.sender {
-            Some(_) => {
-                // get on_ask handler
-                match self.on_ask_handler.get(&msg.type_id()) {
-                    Some(f) => {
-                        f(msg, state, ctx)
-                    },
-                    None => {
-                        // unsupported message types are just dropped silently
-                        // println!("Message type not supported!");
-                        Ok(None)
-                    }
+/// Returns true for message types which are handled by default handlers added by the framework.
+/// These are the same for all behaviors of the same state and thus never conflict when merging.
+fn is_default_handler<S: Send + 'static>(type_id: TypeId) -> bool {
+    type_id == TypeId::of::<ActorManageMessage>()
+        || type_id == TypeId::of::<StateCheckMessage<S>>()
+        || type_id == TypeId::of::<StateSnapshotRequest>()
+}
+
+/// Wraps all handlers except the default handlers with the given interceptors and removes them, such
+/// that the interceptors only apply to these handlers once they are merged with other handlers.
+fn seal_interceptors<S: Send + 'static>(ask: &mut HashMap<TypeId, Handler<S>>, tell: &mut HashMap<TypeId, Handler<S>>, interceptors: &mut Vec<InterceptorFn<S>>) {
+    if interceptors.is_empty() {
+        return;
+    }
+    let interceptors: Arc<[InterceptorFn<S>]> = std::mem::take(interceptors).into();
+    for (type_id, handler) in ask.iter_mut().chain(tell.iter_mut()) {
+        if is_default_handler::<S>(*type_id) {
+            continue;
+        }
+        let inner = handler.clone();
+        let interceptors = interceptors.clone();
+        handler.f = Arc::new(move |msg, state, ctx| {
+            let meta = msg.meta();
+            let next = Next {
+                msg,
+                meta: &meta,
+                handler: &inner,
+                interceptors: &interceptors
+            };
+            next.run(state, ctx)
+        });
+    }
+}
+
+/// Merges the handlers of right into left according to the given rule.
+fn merge_handlers<S: Send + 'static>(left: &mut HashMap<TypeId, Handler<S>>, right: HashMap<TypeId, Handler<S>>, rule: ConflictRule, duplicate: fn(&'static str) -> BehaviorError) -> Result<(), BehaviorError> {
+    for (type_id, handler) in right {
+        match left.entry(type_id) {
+            Entry::Vacant(entry) => {
+                entry.insert(handler);
+            }
+            Entry::Occupied(mut entry) => {
+                if is_default_handler::<S>(type_id) {
+                    continue;
                 }
-            },
-            None => {
-                // get on_tell handler
-                match self.on_tell_handler.get(&msg.type_id()) {
-                    Some(f) => {
-                        f(msg, state, ctx)
-                    },
-                    None => {
-                        // unsupported message types are just dropped silently
-                        // println!("Message type not supported!");
-                        Ok(None)
+                match rule {
+                    ConflictRule::Error => {
+                        return Err(duplicate(handler.type_name));
+                    }
+                    ConflictRule::PreferLeft => {}
+                    ConflictRule::PreferRight => {
+                        entry.insert(handler);
                     }
                 }
             }
         }
     }
+    Ok(())
+}
+
+/// Merges the action of right into left according to the given rule.
+fn merge_action<A>(left: &mut Option<A>, right: Option<A>, rule: ConflictRule, name: &'static str) -> Result<(), BehaviorError> {
+    if let Some(action) = right {
+        if left.is_none() {
+            *left = Some(action);
+        } else {
+            match rule {
+                ConflictRule::Error => {
+                    return Err(BehaviorError::DuplicateAction(name));
+                }
+                ConflictRule::PreferLeft => {}
+                ConflictRule::PreferRight => {
+                    *left = Some(action);
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
+impl<S: Send + 'static> Behavior<S> {
+    /// Returns true if this behavior defines a tell handler for the given message type.
+    pub(crate) fn handles_tell(&self, type_id: TypeId) -> bool {
+        self.on_tell_handler.contains_key(&type_id)
+    }
 
+    /// Returns true if this behavior defines an ask handler for the given message type.
+    pub(crate) fn handles_ask(&self, type_id: TypeId) -> bool {
+        self.on_ask_handler.contains_key(&type_id)
+    }
 
+    /// Returns true if this behavior defines a handler for the given message.
+    pub(crate) fn handles_message(&self, msg: &Message) -> bool {
+        match &msg.sender {
+            Some(_) => self.on_ask_handler.contains_key(&msg.type_id()),
+            None => self.on_tell_handler.contains_key(&msg.type_id())
+        }
+    }
 
+    pub(crate) fn handle(&mut self, msg: Message, state: &mut S, ctx: &mut ActorContext) -> BehaviorAction<S> {
+        // if message contains sender: assume on_ask handler, otherwise on_tell handler
+        let handler = match &msg.sender {
+            Some(_) => {
+                // get on_ask handler
+                self.on_ask_handler.get(&msg.type_id())
+            },
+            None => {
+                // get on_tell handler
+                self.on_tell_handler.get(&msg.type_id())
+            }
+        };
 
+        match handler {
+            Some(f) if is_default_handler::<S>(msg.type_id()) => {
+                // default handlers bypass the interceptors, such that e.g. an interceptor which
+                // rejects unauthorized messages can not keep the actor from being killed
+                (f.f)(msg, state, ctx)
+            }
+            Some(f) => {
+                // run message through interceptors before passing it on to the actual handler
+                let meta = msg.meta();
+                let next = Next {
+                    msg,
+                    meta: &meta,
+                    handler: f,
+                    interceptors: &self.interceptors
+                };
+                next.run(state, ctx)
+            },
+            None => {
+                // unsupported message types are just dropped silently
+                // println!("Message type not supported!");
+                Ok(None)
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Command line client for the admin console of an aector actor system, see the `admin` module.
+//!
+//! Usage:
+//! - `aector-admin <socket> <command...>` sends a single command and prints the response
+//! - `aector-admin <socket>` reads commands line by line from stdin
+
+use std::env;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::process::ExitCode;
+
+/// Sends the given command and prints its response. Returns false if the console answered with an error.
+fn run_command(stream: &mut UnixStream, responses: &mut impl BufRead, command: &str) -> io::Result<bool> {
+    writeln!(stream, "{}", command)?;
+    let streaming = command.split_whitespace().next() == Some("tail");
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if responses.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "admin console closed the connection"));
+        }
+        let response = line.trim_end();
+        if response == "ok" {
+            return Ok(true);
+        }
+        println!("{}", response);
+        if response.starts_with("error:") {
+            return Ok(false);
+        }
+        if streaming {
+            io::stdout().flush()?;
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(socket) = args.first() else {
+        eprintln!("usage: aector-admin <socket> [command...]");
+        return ExitCode::FAILURE;
+    };
+
+    let mut stream = match UnixStream::connect(socket) {
+        Ok(stream) => stream,
+        Err(err) => {
+            eprintln!("failed to connect to {}: {}", socket, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut responses = match stream.try_clone() {
+        Ok(read) => BufReader::new(read),
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = if args.len() > 1 {
+        run_command(&mut stream, &mut responses, &args[1..].join(" "))
+    } else {
+        let mut result = Ok(true);
+        for command in io::stdin().lock().lines() {
+            let command = match command {
+                Ok(command) => command,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            };
+            if command.trim().is_empty() {
+                continue;
+            }
+            result = run_command(&mut stream, &mut responses, &command);
+            if result.is_err() {
+                break;
+            }
+        }
+        result
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Clocks which measure the time of an [ActorSystem](crate::actor_system::ActorSystem). All delays,
+//! i.e. those of [Addr.tell_delayed()](crate::address::Addr#method.tell_delayed),
+//! [Addr.ask_delayed()](crate::address::Addr#method.ask_delayed),
+//! [ActorContext.run_delayed()](crate::actor::ActorContext#method.run_delayed) and
+//! [SuperVisionAction::RestartDelayed](crate::supervision::SuperVisionAction::RestartDelayed), are
+//! measured by the clock of the actor system the actor is spawned on.
+//!
+//! A [Clock::system()] follows the real time. A [Clock::manual()] only advances when it is told to,
+//! e.g. with [ActorSystem.advance()](crate::actor_system::ActorSystem#method.advance), such that tests
+//! of timeouts and backoff run instantly and deterministically.
+//!
+//! Example:
+//! ```
+//! use std::time::Duration;
+//! use aector::actor_system::ActorSystem;
+//! use aector::clock::Clock;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let actor_sys = ActorSystem::with_clock(Clock::manual());
+//! // spawn actors which send delayed messages ...
+//! // fire all delayed messages which are due within the next minute
+//! actor_sys.advance(Duration::from_secs(60)).unwrap();
+//! assert_eq!(actor_sys.clock().now(), Duration::from_secs(60));
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::oneshot;
+
+/// Action which is run once a delay has passed.
+pub(crate) type TimerAction = Box<dyn FnOnce() + Send>;
+
+/// Runs the given action in a separate task once the given delay has passed in real time.
+pub(crate) fn spawn_delayed(delay: Duration, action: TimerAction) {
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        action();
+    });
+}
+
+struct Timer {
+    at: Duration,
+    /// Timers which are due at the same time fire in the order they have been scheduled.
+    seq: u64,
+    action: TimerAction
+}
+
+impl PartialEq for Timer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Timer {}
+
+impl PartialOrd for Timer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed, such that the earliest timer is on top of the heap
+        (other.at, other.seq).cmp(&(self.at, self.seq))
+    }
+}
+
+struct ManualState {
+    now: Duration,
+    timers: BinaryHeap<Timer>,
+    next_seq: u64
+}
+
+enum ClockKind {
+    System {
+        started: Instant
+    },
+    Manual(Mutex<ManualState>)
+}
+
+/// Source of time of an [ActorSystem](crate::actor_system::ActorSystem), see the [module documentation](self).
+pub struct Clock {
+    kind: ClockKind
+}
+
+impl Clock {
+    /// Creates a clock which follows the real time.
+    pub fn system() -> Self {
+        Self {
+            kind: ClockKind::System {
+                started: Instant::now()
+            }
+        }
+    }
+
+    /// Creates a clock which starts at zero and only advances when it is told to.
+    pub fn manual() -> Self {
+        Self {
+            kind: ClockKind::Manual(Mutex::new(ManualState {
+                now: Duration::ZERO,
+                timers: BinaryHeap::new(),
+                next_seq: 0
+            }))
+        }
+    }
+
+    /// Returns true if this clock has been created with [Clock::manual()].
+    pub fn is_manual(&self) -> bool {
+        matches!(self.kind, ClockKind::Manual(_))
+    }
+
+    /// Returns the time which has passed since this clock has been created.
+    pub fn now(&self) -> Duration {
+        match &self.kind {
+            ClockKind::System { started } => started.elapsed(),
+            ClockKind::Manual(state) => state.lock().unwrap().now
+        }
+    }
+
+    /// Waits until the given duration has passed on this clock.
+    pub async fn sleep(&self, duration: Duration) {
+        match &self.kind {
+            ClockKind::System { .. } => {
+                tokio::time::sleep(duration).await;
+            }
+            ClockKind::Manual(_) => {
+                let (tx, rx) = oneshot::channel();
+                self.schedule(duration, Box::new(move || {
+                    let _ = tx.send(());
+                }));
+                // the sender is only dropped without sending if the clock is dropped
+                let _ = rx.await;
+            }
+        }
+    }
+
+    /// Runs the given action once the given delay has passed on this clock.
+    pub(crate) fn schedule(&self, delay: Duration, action: TimerAction) {
+        match &self.kind {
+            ClockKind::System { .. } => {
+                spawn_delayed(delay, action);
+            }
+            ClockKind::Manual(state) => {
+                let mut state = state.lock().unwrap();
+                let timer = Timer {
+                    at: state.now + delay,
+                    seq: state.next_seq,
+                    action
+                };
+                state.next_seq += 1;
+                state.timers.push(timer);
+            }
+        }
+    }
+
+    /// Advances a manual clock by the given duration and fires all timers which are due in the
+    /// meantime in order, including those which are scheduled by fired timers. Returns false for
+    /// system clocks, which can not be advanced.
+    pub(crate) fn advance(&self, duration: Duration) -> bool {
+        let state = match &self.kind {
+            ClockKind::System { .. } => return false,
+            ClockKind::Manual(state) => state
+        };
+        let target = state.lock().unwrap().now + duration;
+        loop {
+            let action = {
+                let mut state = state.lock().unwrap();
+                match state.timers.peek() {
+                    Some(timer) if timer.at <= target => {
+                        let timer = state.timers.pop().unwrap();
+                        state.now = state.now.max(timer.at);
+                        timer.action
+                    }
+                    _ => {
+                        state.now = target;
+                        return true;
+                    }
+                }
+            };
+            // the lock is released, since actions may schedule further timers
+            action();
+        }
+    }
+
+    /// Advances a manual clock to its next timer and fires it. Returns false if no timer is
+    /// pending or this is a system clock.
+    pub(crate) fn fire_next(&self) -> bool {
+        let state = match &self.kind {
+            ClockKind::System { .. } => return false,
+            ClockKind::Manual(state) => state
+        };
+        let action = {
+            let mut state = state.lock().unwrap();
+            match state.timers.pop() {
+                None => return false,
+                Some(timer) => {
+                    state.now = state.now.max(timer.at);
+                    timer.action
+                }
+            }
+        };
+        action();
+        true
+    }
+}
@@ -0,0 +1,101 @@
+//! Topic and type based publish/subscribe of messages between the actors of an [ActorSystem](crate::actor_system::ActorSystem).
+
+use std::any::{Any, TypeId};
+use dashmap::DashMap;
+
+use crate::actor::ActorId;
+use crate::address::Addr;
+use crate::message::BroadcastMessage;
+
+struct Subscriber {
+    actor_id: ActorId,
+    addr: Addr
+}
+
+/// Keeps track of all subscriptions of the actors of an actor system. Subscribers are identified by
+/// the unique id of their actor, such that an actor is subscribed at most once per topic and type.
+pub(crate) struct EventBus {
+    /// Subscribers of messages of a given type published on a given topic.
+    topics: DashMap<(String, TypeId), Vec<Subscriber>>,
+    /// Subscribers of all published messages of a given type regardless of the topic.
+    types: DashMap<TypeId, Vec<Subscriber>>
+}
+
+fn add_subscriber(subscribers: &mut Vec<Subscriber>, actor_id: ActorId, addr: Addr) {
+    if !subscribers.iter().any(|s| s.actor_id == actor_id) {
+        subscribers.push(Subscriber {
+            actor_id,
+            addr
+        });
+    }
+}
+
+impl EventBus {
+    pub(crate) fn new() -> Self {
+        Self {
+            topics: DashMap::new(),
+            types: DashMap::new()
+        }
+    }
+
+    pub(crate) fn subscribe<M: Any + Send>(&self, topic: &str, actor_id: ActorId, addr: Addr) {
+        let mut subscribers = self.topics.entry((topic.to_string(), TypeId::of::<M>())).or_default();
+        add_subscriber(&mut subscribers, actor_id, addr);
+    }
+
+    pub(crate) fn unsubscribe<M: Any + Send>(&self, topic: &str, actor_id: ActorId) {
+        let key = (topic.to_string(), TypeId::of::<M>());
+        if let Some(mut subscribers) = self.topics.get_mut(&key) {
+            subscribers.retain(|s| s.actor_id != actor_id);
+        }
+        self.topics.remove_if(&key, |_, subscribers| subscribers.is_empty());
+    }
+
+    pub(crate) fn subscribe_type<M: Any + Send>(&self, actor_id: ActorId, addr: Addr) {
+        let mut subscribers = self.types.entry(TypeId::of::<M>()).or_default();
+        add_subscriber(&mut subscribers, actor_id, addr);
+    }
+
+    pub(crate) fn unsubscribe_type<M: Any + Send>(&self, actor_id: ActorId) {
+        let key = TypeId::of::<M>();
+        if let Some(mut subscribers) = self.types.get_mut(&key) {
+            subscribers.retain(|s| s.actor_id != actor_id);
+        }
+        self.types.remove_if(&key, |_, subscribers| subscribers.is_empty());
+    }
+
+    /// Removes all subscriptions of the given actor.
+    pub(crate) fn unsubscribe_all(&self, actor_id: ActorId) {
+        for mut subscribers in self.topics.iter_mut() {
+            subscribers.retain(|s| s.actor_id != actor_id);
+        }
+        self.topics.retain(|_, subscribers| !subscribers.is_empty());
+
+        for mut subscribers in self.types.iter_mut() {
+            subscribers.retain(|s| s.actor_id != actor_id);
+        }
+        self.types.retain(|_, subscribers| !subscribers.is_empty());
+    }
+
+    /// Sends the given message to all subscribers of the topic and to all subscribers of its type.
+    /// Actors which are subscribed in both ways receive the message only once.
+    pub(crate) fn publish<M: Any + Send + Clone>(&self, topic: &str, msg: M) {
+        let broadcast_msg = BroadcastMessage::without_sender(msg);
+        let mut delivered = Vec::new();
+
+        if let Some(subscribers) = self.topics.get(&(topic.to_string(), TypeId::of::<M>())) {
+            for subscriber in subscribers.iter() {
+                subscriber.addr.send(broadcast_msg.get_message());
+                delivered.push(subscriber.actor_id);
+            }
+        }
+
+        if let Some(subscribers) = self.types.get(&TypeId::of::<M>()) {
+            for subscriber in subscribers.iter() {
+                if !delivered.contains(&subscriber.actor_id) {
+                    subscriber.addr.send(broadcast_msg.get_message());
+                }
+            }
+        }
+    }
+}
@@ -14,10 +14,24 @@ mod address;
 pub mod actor;
 mod message;
 pub mod behavior;
+pub mod routing;
+pub mod watchdog;
+pub mod clock;
+mod event_bus;
+mod scheduler;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(all(feature = "admin", unix))]
+pub mod admin;
+#[cfg(feature = "recording")]
+pub mod recording;
 
 pub mod testing;
 
-pub use address::Addr;
-pub use message::Message;
+pub use address::{ActorRef, Addr, AskAddr, TellAddr, TypedAddr};
+pub use message::{Envelope, Message, MessageId, MessageMeta};
+
+#[cfg(feature = "macros")]
+pub use aector_macros::actor;
 
 
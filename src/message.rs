@@ -1,6 +1,179 @@
 use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
 
+use tracing::Span;
+
+use crate::actor_system::Terminated;
 use crate::address::Addr;
+use crate::behavior::{ActorManageMessage, StateSnapshot, StateSnapshotRequest};
+use crate::watchdog::StallDetected;
+
+/// Source of the unique ids of messages.
+static NEXT_MESSAGE_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Envelope of the message which is currently handled on this thread, used for propagating the
+    /// correlation id and headers to all messages sent while handling it.
+    static CURRENT_ENVELOPE: RefCell<Option<Envelope>> = const { RefCell::new(None) };
+}
+
+/// Unique id of a [Message].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MessageId(u64);
+
+impl MessageId {
+    fn next() -> Self {
+        MessageId(NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the numerical value of this id.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for MessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Metadata which is attached to every [Message] and can be accessed in handlers with
+/// [ActorContext.current_envelope()](crate::actor::ActorContext#method.current_envelope).
+///
+/// All messages which are sent while an [Actor](crate::actor::Actor) handles a message belong to
+/// the same request chain: they inherit the correlation id and the headers of the handled message,
+/// and its id becomes their causation id. Messages sent from outside of any handler start a new chain,
+/// their correlation id is their own id.
+///
+/// The envelope also carries the [tracing] span in which the message was sent, such that the span
+/// in which the message is handled is linked to it and a request chain shows up as a single trace.
+#[derive(Clone, Debug)]
+pub struct Envelope {
+    id: MessageId,
+    correlation_id: MessageId,
+    causation_id: Option<MessageId>,
+    enqueued_at: SystemTime,
+    headers: HashMap<String, String>,
+    /// Span in which the message was sent, used as parent of the span in which it is handled.
+    span: Span,
+    /// True for messages which are replayed from a recording.
+    replayed: bool
+}
+
+impl Envelope {
+    /// Creates the envelope for a new message, derived from the envelope of the message which is
+    /// currently handled on this thread, if any.
+    fn new() -> Self {
+        let id = MessageId::next();
+        CURRENT_ENVELOPE.with(|current| {
+            match &*current.borrow() {
+                None => {
+                    Envelope {
+                        id,
+                        correlation_id: id,
+                        causation_id: None,
+                        enqueued_at: SystemTime::now(),
+                        headers: HashMap::new(),
+                        span: Span::current(),
+                        replayed: false
+                    }
+                }
+                Some(cause) => {
+                    Envelope {
+                        id,
+                        correlation_id: cause.correlation_id,
+                        causation_id: Some(cause.id),
+                        enqueued_at: SystemTime::now(),
+                        headers: cause.headers.clone(),
+                        span: Span::current(),
+                        replayed: false
+                    }
+                }
+            }
+        })
+    }
+
+    /// Returns the unique id of the message.
+    pub fn id(&self) -> MessageId {
+        self.id
+    }
+
+    /// Returns the id shared by all messages of the request chain this message belongs to.
+    pub fn correlation_id(&self) -> MessageId {
+        self.correlation_id
+    }
+
+    /// Returns the id of the message during whose handling this message was sent.
+    pub fn causation_id(&self) -> Option<MessageId> {
+        self.causation_id
+    }
+
+    /// Returns the point in time at which the message was put into the mailbox of its receiver.
+    pub fn enqueued_at(&self) -> SystemTime {
+        self.enqueued_at
+    }
+
+    /// Returns the value of the header with the given key.
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).map(|value| value.as_str())
+    }
+
+    /// Returns all headers of the message.
+    pub fn headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+
+    /// Returns the span in which the message was sent.
+    pub(crate) fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Returns the envelope of the message which is currently handled on this thread.
+    pub(crate) fn current() -> Option<Envelope> {
+        CURRENT_ENVELOPE.with(|current| current.borrow().clone())
+    }
+
+    /// Returns true if the message which is currently handled on this thread is replayed from a
+    /// recording, see [Replay](crate::recording::Replay).
+    pub(crate) fn current_is_replayed() -> bool {
+        CURRENT_ENVELOPE.with(|current| current.borrow().as_ref().map(|envelope| envelope.replayed).unwrap_or(false))
+    }
+
+    /// Sets a header on the envelope of the message which is currently handled on this thread,
+    /// such that it is propagated to all messages sent afterwards.
+    pub(crate) fn set_current_header(key: String, value: String) {
+        CURRENT_ENVELOPE.with(|current| {
+            if let Some(envelope) = &mut *current.borrow_mut() {
+                envelope.headers.insert(key, value);
+            }
+        });
+    }
+
+    /// Makes the given envelope the current one while running f and restores the previous one afterwards.
+    pub(crate) fn scope<R>(envelope: Envelope, f: impl FnOnce() -> R) -> R {
+        let _guard = EnvelopeGuard {
+            previous: CURRENT_ENVELOPE.with(|current| current.replace(Some(envelope)))
+        };
+        f()
+    }
+}
+
+/// Restores the previous current envelope when dropped, even if the handler panicked.
+struct EnvelopeGuard {
+    previous: Option<Envelope>
+}
+
+impl Drop for EnvelopeGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_ENVELOPE.with(|current| current.replace(previous));
+    }
+}
 
 pub(crate) struct BroadcastMessage<M: Any + Clone + Send> {
     inner: M,
@@ -43,13 +216,49 @@ impl<M: Any + Clone + Send> BroadcastMessage<M> {
 /// dropped after handling the message.
 pub struct Message {
     inner: Box<dyn Any + Send>,
+    type_name: &'static str,
+    envelope: Envelope,
     pub(crate) sender: Option<Addr>
 }
 
+/// Describes a [Message] without giving access to its content. This is passed on to interceptors
+/// (see [BehaviorBuilder.intercept()](crate::behavior::BehaviorBuilder#method.intercept)) since the
+/// message itself is owned by the handler it is dispatched to.
+#[derive(Clone, Copy, Debug)]
+pub struct MessageMeta {
+    type_id: TypeId,
+    type_name: &'static str,
+    is_ask: bool
+}
+
+impl MessageMeta {
+    /// Returns the [TypeId] of the message.
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Returns the name of the type of the message.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns true if the message was sent using ask, i.e. contains the [Addr] of its sender.
+    pub fn is_ask(&self) -> bool {
+        self.is_ask
+    }
+
+    /// Returns true if the message is of type M.
+    pub fn is<M: Any + Send>(&self) -> bool {
+        self.type_id == TypeId::of::<M>()
+    }
+}
+
 impl Message {
     pub(crate) fn with_sender<M: Any + Send>(obj: M, sender: Addr) -> Self {
         Self {
             inner: Box::new(obj),
+            type_name: std::any::type_name::<M>(),
+            envelope: Envelope::new(),
             sender: Some(sender)
         }
     }
@@ -57,10 +266,25 @@ impl Message {
     pub(crate) fn without_sender<M: Any + Send>(obj: M) -> Self {
         Self {
             inner: Box::new(obj),
+            type_name: std::any::type_name::<M>(),
+            envelope: Envelope::new(),
             sender: None
         }
     }
 
+    /// Creates a message which is replayed from a recording.
+    #[cfg(feature = "recording")]
+    pub(crate) fn replayed<M: Any + Send>(obj: M, sender: Option<Addr>) -> Self {
+        let mut envelope = Envelope::new();
+        envelope.replayed = true;
+        Self {
+            inner: Box::new(obj),
+            type_name: std::any::type_name::<M>(),
+            envelope,
+            sender
+        }
+    }
+
     pub(crate) fn instance_of<M: Any + Send>(&self) -> bool {
         self.inner.as_ref().type_id() == TypeId::of::<M>()
     }
@@ -69,6 +293,61 @@ impl Message {
         self.inner.as_ref().type_id()
     }
 
+    pub(crate) fn meta(&self) -> MessageMeta {
+        MessageMeta {
+            type_id: self.type_id(),
+            type_name: self.type_name,
+            is_ask: self.sender.is_some()
+        }
+    }
+
+    /// Returns true if the content of this message is of type M.
+    pub fn is<M: Any + Send>(&self) -> bool {
+        self.instance_of::<M>()
+    }
+
+    /// Returns the name of the type of the content of this message.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns true if this message was sent using ask, i.e. contains the [Addr] of its sender.
+    pub fn is_ask(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Returns true for messages which are meant for the actor itself rather than its behavior, i.e.
+    /// [ActorManageMessage]'s, [Terminated] notifications, notifications of the
+    /// [Watchdog](crate::watchdog::Watchdog) and state snapshots. These are sent through a separate
+    /// control lane of the mailbox, which is always handled before pending data messages.
+    ///
+    /// [StateCheckMessage](crate::behavior::StateCheckMessage)'s are not system messages, so a state
+    /// check is handled in order with the data messages sent before it and observes their effects.
+    /// Only its result is sent back through the control lane.
+    pub fn is_system(&self) -> bool {
+        self.is::<ActorManageMessage>()
+            || self.is::<Terminated>()
+            || self.is::<StallDetected>()
+            || self.is::<StateSnapshotRequest>()
+            || self.is::<StateSnapshot>()
+    }
+
+    /// Returns the [Envelope] of this message.
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    /// Sets the enqueue timestamp of this message to the current time.
+    pub(crate) fn mark_enqueued(&mut self) {
+        self.envelope.enqueued_at = SystemTime::now();
+    }
+
+    /// Returns a reference to the content of this message if it is of type M, e.g. for deciding on
+    /// the priority of a message by its content.
+    pub fn downcast_ref<M: Any + Send>(&self) -> Option<&M> {
+        self.inner.downcast_ref::<M>()
+    }
+
     pub(crate) fn downcast<M: Any + Send>(self) -> Box<M> {
         let inner = self.inner;
 
@@ -0,0 +1,213 @@
+//! Counters and histograms of an [ActorSystem](crate::actor_system::ActorSystem) which are rendered
+//! in the Prometheus text format. This module is only available with the `metrics` feature.
+
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use dashmap::DashMap;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// Upper bounds of the buckets of the handler latency histogram in seconds.
+const LATENCY_BUCKETS: [f64; 8] = [0.00001, 0.0001, 0.001, 0.005, 0.01, 0.1, 1.0, 10.0];
+
+/// Messages of one type handled by one actor.
+#[derive(Default)]
+struct HandlerMetrics {
+    count: AtomicU64,
+    sum_nanos: AtomicU64,
+    /// Number of handled messages per bucket of [LATENCY_BUCKETS], not cumulative.
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()]
+}
+
+impl HandlerMetrics {
+    fn observe(&self, duration: Duration) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_nanos.fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+        let secs = duration.as_secs_f64();
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| secs <= *bound) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Metrics of all actors of an actor system. Mailbox depths are not stored here but read from the
+/// registry of the actor system when rendering.
+#[derive(Default)]
+pub(crate) struct Metrics {
+    /// Handled messages keyed by actor name and message type name.
+    handled: DashMap<(String, &'static str), HandlerMetrics>,
+    /// Restarts keyed by actor name.
+    restarts: DashMap<String, AtomicU64>,
+    dead_letters: AtomicU64,
+    spawned: AtomicU64,
+    stopped: AtomicU64
+}
+
+impl Metrics {
+    pub(crate) fn record_handled(&self, actor: &str, message_type: &'static str, duration: Duration) {
+        // avoid allocating the key for every message once the entry exists
+        if let Some(metrics) = self.handled.get(&(actor.to_string(), message_type)) {
+            metrics.observe(duration);
+            return;
+        }
+        self.handled.entry((actor.to_string(), message_type)).or_default().observe(duration);
+    }
+
+    pub(crate) fn record_restart(&self, actor: &str) {
+        self.restarts.entry(actor.to_string()).or_default().fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_dead_letters(&self, count: u64) {
+        self.dead_letters.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_spawned(&self) {
+        self.spawned.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_stopped(&self) {
+        self.stopped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Renders all metrics and the given mailbox depths per actor in the Prometheus text format.
+    pub(crate) fn render(&self, mailbox_depths: &[(String, usize)]) -> String {
+        let mut out = String::new();
+
+        let mut handled: Vec<_> = self.handled.iter()
+            .map(|entry| (entry.key().clone(), entry.count.load(Ordering::Relaxed)))
+            .collect();
+        handled.sort();
+        out.push_str("# HELP aector_messages_handled_total Number of messages handled per actor and message type.\n");
+        out.push_str("# TYPE aector_messages_handled_total counter\n");
+        for ((actor, message_type), count) in &handled {
+            let _ = writeln!(out, "aector_messages_handled_total{{actor=\"{}\",message_type=\"{}\"}} {}", escape(actor), escape(message_type), count);
+        }
+
+        out.push_str("# HELP aector_handler_duration_seconds Time spent handling messages per actor and message type.\n");
+        out.push_str("# TYPE aector_handler_duration_seconds histogram\n");
+        for ((actor, message_type), _) in &handled {
+            let metrics = match self.handled.get(&(actor.clone(), *message_type)) {
+                Some(metrics) => metrics,
+                None => continue
+            };
+            let labels = format!("actor=\"{}\",message_type=\"{}\"", escape(actor), escape(message_type));
+            let mut cumulative = 0;
+            for (bound, bucket) in LATENCY_BUCKETS.iter().zip(metrics.buckets.iter()) {
+                cumulative += bucket.load(Ordering::Relaxed);
+                let _ = writeln!(out, "aector_handler_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, bound, cumulative);
+            }
+            let count = metrics.count.load(Ordering::Relaxed);
+            let sum = Duration::from_nanos(metrics.sum_nanos.load(Ordering::Relaxed)).as_secs_f64();
+            let _ = writeln!(out, "aector_handler_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, count);
+            let _ = writeln!(out, "aector_handler_duration_seconds_sum{{{}}} {}", labels, sum);
+            let _ = writeln!(out, "aector_handler_duration_seconds_count{{{}}} {}", labels, count);
+        }
+
+        out.push_str("# HELP aector_mailbox_depth Number of messages waiting in the mailbox per actor.\n");
+        out.push_str("# TYPE aector_mailbox_depth gauge\n");
+        for (actor, depth) in mailbox_depths {
+            let _ = writeln!(out, "aector_mailbox_depth{{actor=\"{}\"}} {}", escape(actor), depth);
+        }
+
+        let mut restarts: Vec<_> = self.restarts.iter()
+            .map(|entry| (entry.key().clone(), entry.value().load(Ordering::Relaxed)))
+            .collect();
+        restarts.sort();
+        out.push_str("# HELP aector_actor_restarts_total Number of restarts by supervision per actor.\n");
+        out.push_str("# TYPE aector_actor_restarts_total counter\n");
+        for (actor, count) in &restarts {
+            let _ = writeln!(out, "aector_actor_restarts_total{{actor=\"{}\"}} {}", escape(actor), count);
+        }
+
+        out.push_str("# HELP aector_dead_letters_total Number of messages which were dropped without being handled.\n");
+        out.push_str("# TYPE aector_dead_letters_total counter\n");
+        let _ = writeln!(out, "aector_dead_letters_total {}", self.dead_letters.load(Ordering::Relaxed));
+
+        out.push_str("# HELP aector_actors_spawned_total Number of actors which have been registered.\n");
+        out.push_str("# TYPE aector_actors_spawned_total counter\n");
+        let _ = writeln!(out, "aector_actors_spawned_total {}", self.spawned.load(Ordering::Relaxed));
+
+        out.push_str("# HELP aector_actors_stopped_total Number of actors which have been removed.\n");
+        out.push_str("# TYPE aector_actors_stopped_total counter\n");
+        let _ = writeln!(out, "aector_actors_stopped_total {}", self.stopped.load(Ordering::Relaxed));
+
+        out
+    }
+}
+
+/// Escapes a label value for the Prometheus text format.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Answers HTTP requests on the given listener with the output of render until the actor system is stopped.
+pub(crate) async fn serve<F>(listener: TcpListener, render: F, mut shutdown: watch::Receiver<bool>)
+where
+    F: Fn() -> String + Send + Sync + 'static
+{
+    let render = Arc::new(render);
+    loop {
+        tokio::select! {
+            _ = shutdown.wait_for(|stopped| *stopped) => {
+                info!("Actor system stopped. Closing metrics endpoint");
+                return;
+            }
+            conn = listener.accept() => {
+                match conn {
+                    Ok((stream, _)) => {
+                        let render = render.clone();
+                        tokio::spawn(async move {
+                            if let Err(err) = respond(stream, render.as_ref()).await {
+                                error!("Failed to answer metrics request: {}", err);
+                            }
+                        });
+                    }
+                    Err(err) => {
+                        error!("Failed to accept metrics connection: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reads a single HTTP request and answers it. Only GET /metrics is supported.
+async fn respond<F: Fn() -> String>(mut stream: TcpStream, render: &F) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    // the request body is ignored, only the request line is of interest
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    let response = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = render();
+            format!("HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+        }
+        _ => {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+        }
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Binds a listener for the metrics endpoint on the given local port.
+pub(crate) async fn bind(port: u16) -> std::io::Result<(TcpListener, SocketAddr)> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    let addr = listener.local_addr()?;
+    Ok((listener, addr))
+}
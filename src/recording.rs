@@ -0,0 +1,257 @@
+//! Recording and replay of the messages handled by the actors of an [ActorSystem], such that runs
+//! with nondeterministic message interleaving can be reproduced. This module is only available with
+//! the `recording` feature.
+//!
+//! A [Recorder] writes one JSON object per line for every message handled by a registered actor:
+//! a logical timestamp which orders all messages of the actor system, the name of the actor, the
+//! message type and whether it was sent using ask or tell. The payload is only included for message
+//! types registered with [Recorder.register()](Recorder#method.register). System messages such as
+//! [ActorManageMessage](crate::behavior::ActorManageMessage)'s are not recorded.
+//!
+//! A [Replay] sends the recorded messages to freshly spawned actors with the same names in the same
+//! order, one message at a time. Messages handled by actors of pools or by anonymous actors are
+//! skipped, since their names can not be reused. Messages which are sent by the actors while handling replayed
+//! messages are dropped, since they are part of the recording themselves.
+//!
+//! Example:
+//! ```no_run
+//! use std::time::Duration;
+//! use aector::actor_system::ActorSystem;
+//! use aector::recording::{Recorder, Replay};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Move {
+//!     x: u32,
+//!     y: u32
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! // record a run
+//! let actor_sys = ActorSystem::new();
+//! let recorder = Recorder::create("run.jsonl").unwrap().register::<Move>("Move");
+//! actor_sys.start_recording(recorder).unwrap();
+//! // spawn actors and run the simulation ...
+//!
+//! // replay it on freshly spawned actors
+//! let actor_sys = ActorSystem::new();
+//! // spawn the same actors under the same names ...
+//! let report = Replay::load("run.jsonl").unwrap()
+//!     .register::<Move>("Move")
+//!     .run(&actor_sys, Duration::from_secs(1))
+//!     .await
+//!     .unwrap();
+//! println!("replayed {} messages", report.replayed);
+//! # }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{error, warn};
+
+use crate::actor::Mailbox;
+use crate::actor_system::ActorSystem;
+use crate::address::Addr;
+use crate::message::Message;
+
+/// A single handled message as written to the recording.
+#[derive(Serialize, Deserialize, Debug)]
+struct Record {
+    /// Logical timestamp, the position of the message in the order in which messages were handled.
+    seq: u64,
+    /// Name of the actor which handled the message, [Option::None] for actors of pools and actors
+    /// with names reserved by the actor system, e.g. anonymous actors, which can not be spawned
+    /// under the same name for replaying.
+    actor: Option<String>,
+    /// Registered name of the message type or the name of the Rust type if it is not registered.
+    message_type: String,
+    ask: bool,
+    /// Serialized message, [Option::None] if the message type is not registered.
+    payload: Option<Value>
+}
+
+/// Serializes the content of a message of a registered type.
+type EncodeFn = fn(&Message) -> Option<Value>;
+
+/// Deserializes a message of a registered type, the sender is set for recorded ask messages.
+type DecodeFn = fn(Value, Option<Addr>) -> serde_json::Result<Message>;
+
+fn encode<M: Serialize + Any + Send>(msg: &Message) -> Option<Value> {
+    msg.downcast_ref::<M>().and_then(|m| serde_json::to_value(m).ok())
+}
+
+fn decode<M: DeserializeOwned + Any + Send>(value: Value, sender: Option<Addr>) -> serde_json::Result<Message> {
+    let msg: M = serde_json::from_value(value)?;
+    Ok(Message::replayed(msg, sender))
+}
+
+/// Writes all messages handled by the actors of an [ActorSystem] to a file, started with
+/// [ActorSystem.start_recording()](crate::actor_system::ActorSystem#method.start_recording).
+pub struct Recorder {
+    types: HashMap<TypeId, (String, EncodeFn)>,
+    /// The writer is locked while assigning the logical timestamp, such that records are written in order.
+    out: Mutex<(u64, LineWriter<File>)>
+}
+
+impl Recorder {
+    /// Creates a recorder which writes to the file at the given path. An existing file is truncated.
+    pub fn create<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self {
+            types: HashMap::new(),
+            out: Mutex::new((0, LineWriter::new(file)))
+        })
+    }
+
+    /// Registers the message type M under the given name, such that the content of messages of this
+    /// type is included in the recording.
+    pub fn register<M: Serialize + Any + Send>(mut self, name: &str) -> Self {
+        self.types.insert(TypeId::of::<M>(), (name.to_string(), encode::<M>));
+        self
+    }
+
+    /// Appends the given message which is about to be handled by the given actor to the recording.
+    pub(crate) fn record(&self, actor: Option<&str>, msg: &Message) {
+        if msg.is_system() {
+            return;
+        }
+        let (message_type, payload) = match self.types.get(&msg.type_id()) {
+            Some((name, encode)) => (name.clone(), encode(msg)),
+            None => (msg.type_name().to_string(), None)
+        };
+
+        let mut out = self.out.lock().unwrap();
+        let record = Record {
+            seq: out.0,
+            actor: actor.filter(|actor| !actor.starts_with('$')).map(|actor| actor.to_string()),
+            message_type,
+            ask: msg.is_ask(),
+            payload
+        };
+        out.0 += 1;
+        // serializing plain strings, numbers and json values can not fail
+        let line = serde_json::to_string(&record).unwrap();
+        if let Err(err) = writeln!(out.1, "{}", line) {
+            error!("Failed to write recording: {}", err);
+        }
+    }
+}
+
+/// Error which can occur when loading or running a [Replay].
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ReplayError {
+    #[error("Failed to read recording: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid record in line {0}: {1}")]
+    InvalidRecord(usize, serde_json::Error),
+    #[error("Invalid payload of the recorded message with logical timestamp {0}: {1}")]
+    InvalidPayload(u64, serde_json::Error),
+    #[error("No actor named {0} is registered in the actor system!")]
+    UnknownActor(String),
+    #[error("Actor {0} did not handle the replayed message with logical timestamp {1} in time!")]
+    Timeout(String, u64)
+}
+
+/// Summary of a finished [Replay].
+#[derive(Clone, Debug, Default)]
+pub struct ReplayReport {
+    /// Number of messages which have been replayed.
+    pub replayed: usize,
+    /// Number of recorded messages which could not be replayed, because their type is not registered
+    /// or they were handled by an actor of a pool or an anonymous actor.
+    pub skipped: usize
+}
+
+/// Recorded messages which can be replayed on an [ActorSystem].
+pub struct Replay {
+    records: Vec<Record>,
+    types: HashMap<String, DecodeFn>
+}
+
+impl Replay {
+    /// Loads the recording at the given path.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut records = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line).map_err(|err| ReplayError::InvalidRecord(index + 1, err))?;
+            records.push(record);
+        }
+        records.sort_by_key(|record: &Record| record.seq);
+        Ok(Self {
+            records,
+            types: HashMap::new()
+        })
+    }
+
+    /// Registers the message type M under the given name, which has to be the same name it has been
+    /// registered under when recording.
+    pub fn register<M: DeserializeOwned + Any + Send>(mut self, name: &str) -> Self {
+        self.types.insert(name.to_string(), decode::<M>);
+        self
+    }
+
+    /// Returns the number of recorded messages.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns true if the recording does not contain any message.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Sends all recorded messages to the actors with the recorded names on the given actor system
+    /// in the recorded order. Each message is only sent once the previous one has been handled, waiting
+    /// at most for the given timeout per message. Recorded ask messages are replayed with a sender
+    /// which drops all replies.
+    pub async fn run(self, sys: &Arc<ActorSystem>, timeout: Duration) -> Result<ReplayReport, ReplayError> {
+        let mut report = ReplayReport::default();
+        // replies to replayed ask messages are dropped anyway, the mailbox only has to exist
+        let reply_to = Mailbox::unbounded();
+
+        for record in self.records {
+            let (actor, decode, payload) = match (record.actor, self.types.get(&record.message_type), record.payload) {
+                // payloads of unit structs are serialized as null, which is read back as no payload
+                (Some(actor), Some(decode), payload) => (actor, decode, payload.unwrap_or(Value::Null)),
+                (actor, _, _) => {
+                    warn!("Skipping recorded message {} of type {} for actor {}", record.seq, record.message_type, actor.as_deref().unwrap_or("anonymous"));
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+
+            let addr = sys.query(&actor).ok_or_else(|| ReplayError::UnknownActor(actor.clone()))?;
+            let sender = if record.ask { Some(reply_to.get_addr()) } else { None };
+            let msg = decode(payload, sender).map_err(|err| ReplayError::InvalidPayload(record.seq, err))?;
+
+            let handled = addr.stats().handled();
+            addr.send(msg);
+            let deadline = Instant::now() + timeout;
+            while addr.stats().handled() == handled {
+                if Instant::now() > deadline {
+                    return Err(ReplayError::Timeout(actor, record.seq));
+                }
+                tokio::task::yield_now().await;
+            }
+            report.replayed += 1;
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,488 @@
+//! This module contains the routing logics which can be used for pools of identical actors. A pool
+//! is spawned with [ActorSystem.spawn_pool()](crate::actor_system::ActorSystem#method.spawn_pool) and is
+//! reachable through the single [Addr] of its router, which forwards each message to one of its routees:
+//! ```
+//! use aector::actor::{Actor, MailboxType};
+//! use aector::actor_system::ActorSystem;
+//! use aector::behavior::BehaviorBuilder;
+//! use aector::routing::RoutingLogic;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let actor_sys = ActorSystem::new();
+//! let factory = || Actor::new((), BehaviorBuilder::new().build(), MailboxType::Unbounded);
+//! actor_sys.spawn_pool(factory, 4, RoutingLogic::RoundRobin, "workers".to_string()).unwrap();
+//! let router = actor_sys.query("workers").unwrap();
+//! # }
+//! ```
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use tokio::sync::watch;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+use tracing::info;
+
+use crate::actor::{ActorId, Mailbox};
+use crate::address::Addr;
+use crate::behavior::ActorManageMessage;
+use crate::message::Message;
+use crate::scheduler::Scheduler;
+
+/// Extracts the hash key of a message which is used for consistent hashing. Returns [Option::None]
+/// for messages without a key.
+type KeyExtractor = Arc<dyn Fn(&Message) -> Option<u64> + Send + Sync>;
+
+/// Creates and spawns a new routee of a resizable pool and returns its address.
+pub(crate) type RouteeSpawner = Box<dyn FnMut() -> Addr + Send>;
+
+/// Number of points each routee occupies on the hash ring. More points spread the keys more evenly.
+const VIRTUAL_NODES: u64 = 32;
+
+/// Describes how a router picks the routee a message is forwarded to.
+#[non_exhaustive]
+pub enum RoutingLogic {
+    /// Messages are forwarded to the routees in turn.
+    RoundRobin,
+    /// Messages are forwarded to a randomly chosen routee.
+    Random,
+    /// Messages are forwarded to the routee with the fewest messages waiting in its mailbox.
+    SmallestMailbox,
+    /// Messages with the same key are always forwarded to the same routee as long as the routees
+    /// do not change. Use [RoutingLogic::consistent_hash] to create this variant.
+    ConsistentHash(KeyExtractor)
+}
+
+impl RoutingLogic {
+    /// Creates a consistent hashing logic where the key of messages of type M is extracted with the
+    /// given function. Messages of other types are forwarded in a round robin fashion.
+    pub fn consistent_hash<M: Any + Send, K: Hash + 'static>(key: fn(&M) -> K) -> Self {
+        RoutingLogic::ConsistentHash(Arc::new(move |msg: &Message| {
+            msg.downcast_ref::<M>().map(|m| hash_of(&key(m)))
+        }))
+    }
+}
+
+/// Decides how many routees a resizable pool should have. A [Resizer] is passed on to
+/// [ActorSystem.spawn_resizable_pool()](crate::actor_system::ActorSystem#method.spawn_resizable_pool)
+/// and periodically compares the load of the pool against its thresholds. The pool grows if the
+/// average number of messages waiting per routee reaches the pressure threshold or the average time
+/// spent handling a message reaches the latency threshold. It shrinks by one routee at a time if
+/// no messages are waiting and the routees were busy for less than the backoff threshold of the
+/// time. Surplus routees are stopped gracefully, i.e. their kill message is queued behind the
+/// messages already in their mailboxes, which they handle before exiting.
+#[derive(Clone, Debug)]
+pub struct Resizer {
+    min: usize,
+    max: usize,
+    pressure_threshold: usize,
+    latency_threshold: Option<Duration>,
+    backoff_threshold: f64,
+    interval: Duration
+}
+
+impl Resizer {
+    /// Creates a resizer which keeps the number of routees between min and max, where a pool always
+    /// consists of at least one routee. By default the
+    /// pool grows once 10 messages are waiting per routee, shrinks if its routees were busy less than
+    /// 30% of the time, does not consider handler latency and is checked every second.
+    pub fn new(min: usize, max: usize) -> Self {
+        Self {
+            min: min.max(1),
+            max,
+            pressure_threshold: 10,
+            latency_threshold: None,
+            backoff_threshold: 0.3,
+            interval: Duration::from_secs(1)
+        }
+    }
+
+    /// Sets the average number of waiting messages per routee at which the pool grows.
+    pub fn with_pressure_threshold(mut self, messages_per_routee: usize) -> Self {
+        self.pressure_threshold = messages_per_routee.max(1);
+        self
+    }
+
+    /// Sets the average time spent handling a single message at which the pool grows.
+    pub fn with_latency_threshold(mut self, latency: Duration) -> Self {
+        self.latency_threshold = Some(latency);
+        self
+    }
+
+    /// Sets the fraction of time (between 0 and 1) the routees have to be busy on average for the
+    /// pool not to shrink.
+    pub fn with_backoff_threshold(mut self, utilization: f64) -> Self {
+        self.backoff_threshold = utilization;
+        self
+    }
+
+    /// Sets the interval in which the load of the pool is checked, which is at least one millisecond.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(Duration::from_millis(1));
+        self
+    }
+
+    pub(crate) fn min(&self) -> usize {
+        self.min
+    }
+
+    pub(crate) fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Returns the new size of the pool and the reason for resizing, or [Option::None] if the pool
+    /// should keep its size. handled and handling_time are counted since the last check.
+    fn decide(&self, size: usize, queued: usize, handled: u64, handling_time: Duration, elapsed: Duration) -> Option<(usize, &'static str)> {
+        if size < self.min {
+            return Some((self.min, "below minimum"));
+        }
+
+        let pressure = queued >= self.pressure_threshold * size;
+        let latency = match self.latency_threshold {
+            Some(threshold) if handled > 0 => handling_time.as_secs_f64() / handled as f64 >= threshold.as_secs_f64(),
+            _ => false
+        };
+        if (pressure || latency) && size < self.max {
+            // grow at least by one, or to as many routees as needed to get below the pressure threshold
+            let needed = queued.div_ceil(self.pressure_threshold);
+            let new_size = needed.max(size + 1).min(self.max);
+            let reason = if pressure { "mailbox pressure" } else { "handler latency" };
+            return Some((new_size, reason));
+        }
+
+        let available = elapsed.as_secs_f64() * size as f64;
+        let utilization = if available > 0.0 { handling_time.as_secs_f64() / available } else { 0.0 };
+        if queued == 0 && utilization < self.backoff_threshold && size > self.min {
+            return Some((size - 1, "low utilization"));
+        }
+
+        None
+    }
+}
+
+fn hash_of<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+struct Routee {
+    id: u64,
+    addr: Addr,
+    /// Counters of the routee at the last check of the resizer.
+    last_handled: u64,
+    last_handling_time: Duration
+}
+
+/// Forwards messages to a set of routees according to a [RoutingLogic].
+pub(crate) struct Router {
+    logic: RoutingLogic,
+    routees: Vec<Routee>,
+    /// Maps points on the hash ring to routee ids, only used for [RoutingLogic::ConsistentHash].
+    ring: BTreeMap<u64, u64>,
+    next_routee_id: u64,
+    round_robin_idx: usize,
+    resizer: Option<(Resizer, RouteeSpawner)>,
+    /// Scheduler of a deterministic actor system, whose random number generator is used for
+    /// [RoutingLogic::Random].
+    scheduler: Option<Arc<Scheduler>>
+}
+
+impl Router {
+    pub(crate) fn new(logic: RoutingLogic) -> Self {
+        Self {
+            logic,
+            routees: Vec::new(),
+            ring: BTreeMap::new(),
+            next_routee_id: 0,
+            round_robin_idx: 0,
+            resizer: None,
+            scheduler: None
+        }
+    }
+
+    /// Creates a router for a resizable pool with the minimum number of routees. Further routees
+    /// are added by the resizer using the given spawner.
+    pub(crate) fn resizable(logic: RoutingLogic, resizer: Resizer, mut spawner: RouteeSpawner) -> Self {
+        let mut router = Router::new(logic);
+        for _ in 0..resizer.min {
+            router.add_routee(spawner());
+        }
+        router.resizer = Some((resizer, spawner));
+        router
+    }
+
+    pub(crate) fn add_routee(&mut self, addr: Addr) {
+        let id = self.next_routee_id;
+        self.next_routee_id += 1;
+
+        for vnode in 0..VIRTUAL_NODES {
+            self.ring.insert(hash_of(&(id, vnode)), id);
+        }
+        let stats = addr.stats();
+        let last_handled = stats.handled();
+        let last_handling_time = stats.handling_time();
+        self.routees.push(Routee {
+            id,
+            addr,
+            last_handled,
+            last_handling_time
+        });
+    }
+
+    fn remove_routee(&mut self, idx: usize) -> Addr {
+        let routee = self.routees.remove(idx);
+        self.ring.retain(|_, id| *id != routee.id);
+        routee.addr
+    }
+
+    /// Removes all routees which have exited on their own.
+    fn remove_closed_routees(&mut self) {
+        while let Some(idx) = self.routees.iter().position(|routee| routee.addr.is_closed()) {
+            self.remove_routee(idx);
+        }
+    }
+
+    /// Returns the index of the routee with the fewest messages waiting in its mailbox.
+    fn smallest_mailbox(&self) -> usize {
+        let mut smallest = 0;
+        for (idx, routee) in self.routees.iter().enumerate() {
+            if routee.addr.mailbox_len() < self.routees[smallest].addr.mailbox_len() {
+                smallest = idx;
+            }
+        }
+        smallest
+    }
+
+    fn round_robin(&mut self) -> usize {
+        let idx = self.round_robin_idx % self.routees.len();
+        self.round_robin_idx = idx + 1;
+        idx
+    }
+
+    /// Returns the index of the routee the given message is to be forwarded to. Must only be called
+    /// if there is at least one routee.
+    fn select(&mut self, msg: &Message) -> usize {
+        match &self.logic {
+            RoutingLogic::RoundRobin => {
+                self.round_robin()
+            }
+            RoutingLogic::Random => {
+                match &self.scheduler {
+                    Some(scheduler) => scheduler.gen_index(self.routees.len()),
+                    None => rand::thread_rng().gen_range(0..self.routees.len())
+                }
+            }
+            RoutingLogic::SmallestMailbox => {
+                self.smallest_mailbox()
+            }
+            RoutingLogic::ConsistentHash(key_extractor) => {
+                match key_extractor(msg) {
+                    Some(key) => {
+                        // first point on the ring at or after the key, wrapping around at the end
+                        let routee_id = self.ring.range(key..).next()
+                            .or_else(|| self.ring.iter().next())
+                            .map(|(_, id)| *id);
+                        routee_id
+                            .and_then(|id| self.routees.iter().position(|routee| routee.id == id))
+                            .unwrap_or(0)
+                    }
+                    None => {
+                        self.round_robin()
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forwards the given message to one routee. The message is passed on as is, so the original
+    /// sender is preserved for ask messages.
+    fn route(&mut self, msg: Message) {
+        let idx = self.select(&msg);
+        self.routees[idx].addr.send(msg);
+    }
+
+    fn broadcast(&self, msg: ActorManageMessage) {
+        for routee in self.routees.iter() {
+            routee.addr.tell(msg);
+        }
+    }
+
+    /// Checks the load of the pool and adds or removes routees as decided by the resizer.
+    fn resize(&mut self, pool_name: &str, elapsed: Duration) {
+        self.remove_closed_routees();
+
+        let mut queued = 0;
+        let mut handled = 0;
+        let mut handling_time = Duration::ZERO;
+        for routee in self.routees.iter_mut() {
+            let stats = routee.addr.stats();
+            queued += stats.queued();
+            handled += stats.handled() - routee.last_handled;
+            handling_time += stats.handling_time() - routee.last_handling_time;
+            routee.last_handled = stats.handled();
+            routee.last_handling_time = stats.handling_time();
+        }
+
+        let size = self.routees.len();
+        let decision = match &self.resizer {
+            Some((resizer, _)) => resizer.decide(size, queued, handled, handling_time, elapsed),
+            None => None
+        };
+
+        if let Some((new_size, reason)) = decision {
+            info!(pool = pool_name, from = size, to = new_size, queued, handled, reason, "Resizing pool");
+            while self.routees.len() < new_size {
+                let addr = match &mut self.resizer {
+                    Some((_, spawner)) => spawner(),
+                    None => return
+                };
+                self.add_routee(addr);
+            }
+            while self.routees.len() > new_size {
+                // the least loaded routee handles its remaining messages before it exits, since the
+                // kill message is queued behind them instead of being sent through the control lane
+                let smallest = self.smallest_mailbox();
+                let addr = self.remove_routee(smallest);
+                addr.send_data(Message::without_sender(ActorManageMessage::Kill));
+            }
+        }
+    }
+
+    /// Runs the router until it is killed, all routees have exited or the actor system is stopped.
+    /// On deterministic actor systems the router waits for its turn before each message.
+    pub(crate) async fn run(mut self, pool_name: String, mut mailbox: Mailbox, mut shutdown: watch::Receiver<bool>, scheduler: Option<(Arc<Scheduler>, ActorId)>) {
+        self.scheduler = scheduler.as_ref().map(|(scheduler, _)| scheduler.clone());
+        let mut resize_interval = self.resizer.as_ref().map(|(resizer, _)| {
+            let mut resize_interval = interval(resizer.interval);
+            resize_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            resize_interval
+        });
+        let mut last_resize = Instant::now();
+
+        loop {
+            if let Some((scheduler, id)) = &scheduler {
+                tokio::select! {
+                    biased;
+                    _ = shutdown.wait_for(|stopped| *stopped) => {
+                        return;
+                    }
+                    _ = scheduler.turn(*id) => {}
+                }
+            }
+
+            let msg = tokio::select! {
+                biased;
+                _ = shutdown.wait_for(|stopped| *stopped) => {
+                    // routees are stopped by the actor system themselves
+                    return;
+                }
+                _ = tick(&mut resize_interval) => {
+                    self.resize(&pool_name, last_resize.elapsed());
+                    last_resize = Instant::now();
+                    continue;
+                }
+                msg = mailbox.recv() => msg
+            };
+
+            let msg = match msg {
+                Some(msg) => msg,
+                None => return
+            };
+
+            self.remove_closed_routees();
+            if self.routees.is_empty() {
+                return;
+            }
+
+            if msg.instance_of::<ActorManageMessage>() {
+                // manage messages are meant for the whole pool
+                let manage_msg = *msg.downcast::<ActorManageMessage>();
+                self.broadcast(manage_msg);
+                if let ActorManageMessage::Kill = manage_msg {
+                    return;
+                }
+            } else {
+                self.route(msg);
+            }
+        }
+    }
+}
+
+/// Completes on the next tick of the given interval or never if there is no interval.
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Resizer;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn resizer_grows_on_mailbox_pressure() {
+        let resizer = Resizer::new(1, 10).with_pressure_threshold(5);
+        // 2 routees with 9 waiting messages are below the threshold of 5 per routee
+        assert_eq!(resizer.decide(2, 9, 10, SECOND, SECOND), None);
+        // 2 routees with 10 waiting messages grow by one
+        assert_eq!(resizer.decide(2, 10, 10, SECOND, SECOND), Some((3, "mailbox pressure")));
+        // 23 waiting messages need 5 routees to get below the threshold
+        assert_eq!(resizer.decide(2, 23, 10, SECOND, SECOND), Some((5, "mailbox pressure")));
+    }
+
+    #[test]
+    fn resizer_grows_on_handler_latency() {
+        let resizer = Resizer::new(1, 10).with_latency_threshold(Duration::from_millis(100));
+        // 10 messages took 50ms each
+        assert_eq!(resizer.decide(2, 1, 10, Duration::from_millis(500), SECOND), None);
+        // 10 messages took 100ms each
+        assert_eq!(resizer.decide(2, 1, 10, SECOND, SECOND), Some((3, "handler latency")));
+        // the latency is not considered if no message has been handled
+        assert_eq!(resizer.decide(2, 1, 0, SECOND, SECOND), None);
+    }
+
+    #[test]
+    fn resizer_shrinks_on_low_utilization() {
+        let resizer = Resizer::new(1, 10).with_backoff_threshold(0.5);
+        // 2 routees busy for 0.5s out of 2s are utilized 25% of the time
+        assert_eq!(resizer.decide(2, 0, 10, Duration::from_millis(500), SECOND), Some((1, "low utilization")));
+        // 2 routees busy for 1.5s out of 2s are utilized 75% of the time
+        assert_eq!(resizer.decide(2, 0, 10, Duration::from_millis(1500), SECOND), None);
+        // the pool does not shrink while messages are waiting
+        assert_eq!(resizer.decide(2, 1, 10, Duration::ZERO, SECOND), None);
+    }
+
+    #[test]
+    fn resizer_keeps_size_within_bounds() {
+        let resizer = Resizer::new(2, 4).with_pressure_threshold(1);
+        assert_eq!(resizer.decide(1, 0, 0, Duration::ZERO, SECOND), Some((2, "below minimum")));
+        // the pool does not grow beyond the maximum, no matter the pressure
+        assert_eq!(resizer.decide(3, 100, 0, Duration::ZERO, SECOND), Some((4, "mailbox pressure")));
+        assert_eq!(resizer.decide(4, 100, 0, Duration::ZERO, SECOND), None);
+        // the pool does not shrink below the minimum
+        assert_eq!(resizer.decide(2, 0, 0, Duration::ZERO, SECOND), None);
+        // a pool always consists of at least one routee
+        assert_eq!(Resizer::new(0, 4).min(), 1);
+    }
+
+    #[test]
+    fn resizer_interval_is_not_zero() {
+        let resizer = Resizer::new(1, 4).with_interval(Duration::ZERO);
+        assert_eq!(resizer.interval, Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,205 @@
+//! Deterministic scheduling of the actors of an [ActorSystem](crate::actor_system::ActorSystem) created
+//! with [ActorSystem::deterministic()](crate::actor_system::ActorSystem#method.deterministic).
+//!
+//! The actors of a deterministic actor system still run as tokio tasks, but only one of them is
+//! allowed to run at a time: before starting and before handling each message an actor waits for
+//! its turn. The scheduler grants the next turn once the current one has ended, choosing among all
+//! actors with pending messages using a seeded random number generator. Since handlers are
+//! synchronous, all messages sent during a turn have arrived in their mailboxes once it ends, such
+//! that the same seed always yields the same interleaving. Delays are measured by the manual
+//! [Clock] of the actor system, which is advanced to its next timer once no actor is ready.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::sync::{watch, Notify};
+use tokio::time::sleep;
+
+use crate::actor::ActorId;
+use crate::address::Addr;
+use crate::clock::Clock;
+
+/// Interval in which an idle scheduler checks for messages sent from outside of the actor system.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Actor or router which is scheduled by a [Scheduler].
+struct Participant {
+    id: ActorId,
+    addr: Addr,
+    /// True if the participant wants a turn without having pending messages, i.e. for starting.
+    wants_turn: bool,
+    /// True while the participant waits for a timer, it is not granted any turn in the meantime.
+    sleeping: bool,
+    granted: Arc<Notify>
+}
+
+struct SchedulerState {
+    rng: StdRng,
+    /// All participants in the order they have been registered.
+    participants: Vec<Participant>,
+    /// Participant whose turn it currently is.
+    holder: Option<ActorId>
+}
+
+pub(crate) struct Scheduler {
+    seed: u64,
+    /// Shared with the timers which wake up sleeping participants.
+    state: Arc<Mutex<SchedulerState>>,
+    /// Manual clock of the actor system, which measures the virtual time.
+    clock: Arc<Clock>,
+    /// Notified whenever a turn ends.
+    turn_ended: Notify
+}
+
+impl Scheduler {
+    pub(crate) fn new(seed: u64, clock: Arc<Clock>) -> Self {
+        Self {
+            seed,
+            state: Arc::new(Mutex::new(SchedulerState {
+                rng: StdRng::seed_from_u64(seed),
+                participants: Vec::new(),
+                holder: None
+            })),
+            clock,
+            turn_ended: Notify::new()
+        }
+    }
+
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns a random index below len drawn from the seeded random number generator, such that
+    /// random decisions taken during a turn are the same on every run.
+    pub(crate) fn gen_index(&self, len: usize) -> usize {
+        self.state.lock().unwrap().rng.gen_range(0..len)
+    }
+
+    /// Adds the given participant, which is granted a turn for starting if wants_turn is set.
+    pub(crate) fn register(&self, id: ActorId, addr: Addr, wants_turn: bool) {
+        self.state.lock().unwrap().participants.push(Participant {
+            id,
+            addr,
+            wants_turn,
+            sleeping: false,
+            granted: Arc::new(Notify::new())
+        });
+    }
+
+    /// Removes the given participant and ends its turn if it currently has one.
+    pub(crate) fn deregister(&self, id: ActorId) {
+        let mut state = self.state.lock().unwrap();
+        state.participants.retain(|p| p.id != id);
+        if state.holder == Some(id) {
+            state.holder = None;
+            self.turn_ended.notify_one();
+        }
+    }
+
+    /// Returns true if it currently is the turn of the given participant.
+    pub(crate) fn is_holding(&self, id: ActorId) -> bool {
+        self.state.lock().unwrap().holder == Some(id)
+    }
+
+    /// Ends the current turn of the given participant if it has one and waits for its next turn.
+    /// Returns immediately for participants which are not registered.
+    pub(crate) async fn turn(&self, id: ActorId) {
+        let granted = {
+            let mut state = self.state.lock().unwrap();
+            self.end_turn(&mut state, id);
+            match state.participants.iter().find(|p| p.id == id) {
+                Some(participant) => participant.granted.clone(),
+                None => return
+            }
+        };
+        granted.notified().await;
+    }
+
+    /// Ends the current turn of the given participant and waits for the given duration of virtual
+    /// time before it is granted its next turn.
+    pub(crate) async fn sleep(&self, id: ActorId, duration: Duration) {
+        let granted = {
+            let mut state = self.state.lock().unwrap();
+            self.end_turn(&mut state, id);
+            match state.participants.iter_mut().find(|p| p.id == id) {
+                Some(participant) => {
+                    participant.sleeping = true;
+                    participant.granted.clone()
+                }
+                None => return
+            }
+        };
+        // the participant is woken up while the timer fires, such that it is ready right afterwards
+        let state = self.state.clone();
+        self.clock.schedule(duration, Box::new(move || {
+            if let Some(participant) = state.lock().unwrap().participants.iter_mut().find(|p| p.id == id) {
+                participant.sleeping = false;
+                participant.wants_turn = true;
+            }
+        }));
+        granted.notified().await;
+    }
+
+    fn end_turn(&self, state: &mut SchedulerState, id: ActorId) {
+        if state.holder == Some(id) {
+            state.holder = None;
+            self.turn_ended.notify_one();
+        }
+    }
+
+    /// Grants turns until the given shutdown receiver is notified. If until_idle is set, returns
+    /// once no participant is ready and no timer is pending. Otherwise waits for messages sent
+    /// from outside of the actor system while idle and returns once all participants have exited.
+    pub(crate) async fn run(&self, until_idle: bool, shutdown: &mut watch::Receiver<bool>) {
+        loop {
+            // wait for the current turn to end
+            while self.state.lock().unwrap().holder.is_some() {
+                tokio::select! {
+                    biased;
+                    _ = shutdown.wait_for(|stopped| *stopped) => {
+                        return;
+                    }
+                    _ = self.turn_ended.notified() => {}
+                }
+            }
+
+            // if no participant is ready, the virtual time is advanced to the next timer
+            if self.grant_turn() || self.clock.fire_next() {
+                continue;
+            }
+            if until_idle || self.state.lock().unwrap().participants.is_empty() {
+                return;
+            }
+            tokio::select! {
+                biased;
+                _ = shutdown.wait_for(|stopped| *stopped) => {
+                    return;
+                }
+                _ = sleep(IDLE_POLL_INTERVAL) => {}
+            }
+        }
+    }
+
+    /// Grants the next turn to a randomly chosen ready participant. Returns false if none is ready.
+    fn grant_turn(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let ready: Vec<usize> = state.participants.iter()
+            .enumerate()
+            .filter(|(_, p)| !p.sleeping && (p.wants_turn || p.addr.stats().pending() > 0))
+            .map(|(index, _)| index)
+            .collect();
+
+        if ready.is_empty() {
+            return false;
+        }
+        let index = ready[state.rng.gen_range(0..ready.len())];
+        let participant = &mut state.participants[index];
+        participant.wants_turn = false;
+        participant.granted.notify_one();
+        let id = participant.id;
+        state.holder = Some(id);
+        true
+    }
+}
@@ -1,3 +1,5 @@
+use tracing::info;
+
 use crate::actor::{Actor, ExitReason};
 use crate::actor::Backup;
 use crate::supervision::supervision::{SuperVisionAction, SupervisionStrategy};
@@ -21,6 +23,10 @@ impl<S: Send + Clone> SupervisionStrategy<S> for SimpleRestartStrategy {
                 println!("ActorSys: actor died on purpose");
                 return Exit;
             }
+            ExitReason::Shutdown => {
+                info!("Actor system has been stopped. Stopping supervised actor");
+                return Exit;
+            }
             ExitReason::Restart => {
                 println!("ActorSys: actor requested a restart. Restarting actor with initial state and behavior");
                 actor.apply_backup(&backup);
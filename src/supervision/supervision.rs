@@ -5,6 +5,7 @@ use crate::actor::Backup;
 
 /// Represents decision of SuperVisionStrategy
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum SuperVisionAction {
     Exit,
     Restart,
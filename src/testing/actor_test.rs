@@ -1,7 +1,10 @@
 use std::any::{Any, TypeId};
 use std::collections::{VecDeque};
-use std::fmt::{Debug, Formatter};
-use crate::actor::{Actor, MailboxType};
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::actor::{Actor, ExitReason, MailboxType};
+use crate::actor_system::{DeadLetter, DEAD_LETTERS_TOPIC};
 use crate::{Addr, Message};
 use crate::behavior::{Behavior, BehaviorBuilder, BehaviorAction, StateCheckMessage};
 use crate::testing::actor_test::ResponseDyn::Check;
@@ -9,16 +12,66 @@ use crate::testing::actor_test::TestActorState::{PendingResponse, Ready};
 use thiserror::Error;
 
 
-#[derive(Error, Debug)]
-enum ActorTestError {
+/// Time a [TestActor] waits for a response before failing the test, unless configured otherwise
+/// with [ActorTestBuilder.timeout()](ActorTestBuilder#method.timeout).
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Reason why a test task failed.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ActorTestError {
     #[error("Invalid message order")]
     InvalidMessageOrder,
     #[error("Given criteria not fulfilled")]
     CriteriaNotMet,
     #[error("State check failed")]
-    StateCheckFailed
+    StateCheckFailed,
+    #[error("No response received in time")]
+    Timeout,
+    #[error("Test actor stopped before the test was finished")]
+    Aborted
+}
+
+/// Result of a successful test, returned by [ActorSystem.spawn_test()](crate::actor_system::ActorSystem#method.spawn_test).
+#[derive(Clone, Debug)]
+pub struct TestReport {
+    /// Log of all executed steps.
+    pub steps: Vec<String>
+}
+
+/// Describes why a test failed, returned by [ActorSystem.spawn_test()](crate::actor_system::ActorSystem#method.spawn_test).
+#[derive(Clone, Debug)]
+pub struct TestFailure {
+    /// Kind and id of the failed task, e.g. `Ask #3`.
+    pub task: String,
+    pub reason: ActorTestError,
+    /// Name of the message type which was expected by the failed task, if any.
+    pub expected: Option<&'static str>,
+    /// Name of the message type which was received instead, if any.
+    pub received: Option<&'static str>,
+    /// Log of all steps which were executed before the failed task.
+    pub steps: Vec<String>
+}
+
+impl Display for TestFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} failed: {}", self.task, self.reason)?;
+        if let Some(expected) = self.expected {
+            write!(f, "\n  expected: {}", expected)?;
+        }
+        if let Some(received) = self.received {
+            write!(f, "\n  received: {}", received)?;
+        }
+        write!(f, "\nsteps:")?;
+        for step in &self.steps {
+            write!(f, "\n  {}", step)?;
+        }
+        Ok(())
+    }
 }
 
+impl std::error::Error for TestFailure {}
+
 /// This type represents an expected message response.
 pub enum Response<M: Any + Send> {
     Ask(fn(M) -> bool),
@@ -32,14 +85,15 @@ where
 {
     fn from(res_t: Response<M>) -> Self {
         let type_id = TypeId::of::<M>();
+        let type_name = std::any::type_name::<M>();
         match res_t {
             Response::Ask(criteria) => {
                 let crit_wrapper = ResponseDyn::wrap(criteria);
-                ResponseDyn::Ask(type_id, crit_wrapper)
+                ResponseDyn::Ask(type_id, type_name, crit_wrapper)
             }
             Response::Tell(criteria) => {
                 let crit_wrapper = ResponseDyn::wrap(criteria);
-                ResponseDyn::Tell(type_id, crit_wrapper)
+                ResponseDyn::Tell(type_id, type_name, crit_wrapper)
             }
             Response::Check => {
                 ResponseDyn::Check
@@ -51,8 +105,8 @@ where
 /// Dynamically typed responses. Only used to store responses internally (since generics cant be
 /// directly stored in Vec)
 enum ResponseDyn {
-    Ask(TypeId, Box<dyn Fn(Message) -> bool + Send>),
-    Tell(TypeId, Box<dyn Fn(Message) -> bool + Send>),
+    Ask(TypeId, &'static str, Box<dyn Fn(Message) -> bool + Send>),
+    Tell(TypeId, &'static str, Box<dyn Fn(Message) -> bool + Send>),
     Check
 }
 
@@ -60,7 +114,15 @@ impl ResponseDyn {
     /// Wraps a given type and criteria into a dynamically typed enum
     pub fn tell<M: Any + Send>(criteria: fn(M) -> bool) -> ResponseDyn {
         let crit_wrapped = Self::wrap(criteria);
-        ResponseDyn::Tell(TypeId::of::<M>(), crit_wrapped)
+        ResponseDyn::Tell(TypeId::of::<M>(), std::any::type_name::<M>(), crit_wrapped)
+    }
+
+    /// Returns the name of the expected message type.
+    fn expected(&self) -> Option<&'static str> {
+        match self {
+            ResponseDyn::Ask(_, type_name, _) | ResponseDyn::Tell(_, type_name, _) => Some(type_name),
+            Check => None
+        }
     }
 
     /// Wraps the given, generically typed closure into a dynamically typed, boxed closure.
@@ -96,6 +158,16 @@ enum TestTask<S> {
     Exit
 }
 
+impl<S> TestTask<S> {
+    /// Returns the id of this task. The exit task never waits for a response and has no id of its own.
+    fn id(&self) -> u32 {
+        match self {
+            TestTask::Tell(_, nr) | TestTask::Ask(_, _, nr) | TestTask::Check(_, nr) | TestTask::Expect(_, nr) => *nr,
+            TestTask::Exit => u32::MAX
+        }
+    }
+}
+
 impl<S> Debug for TestTask<S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -119,16 +191,50 @@ impl<S> Debug for TestTask<S> {
     }
 }
 
+/// Progress of a [TestActor], which is shared with the actor system such that the failed task and
+/// the executed steps can still be reported if the test actor panics, e.g. in a criteria function.
+#[derive(Default)]
+pub(crate) struct TestProgress {
+    /// Id and label of the task which is currently run.
+    current: Option<(u32, String)>,
+    /// Log of all executed steps.
+    steps: Vec<String>
+}
+
+impl TestProgress {
+    /// Returns the label of the task which is currently run.
+    fn task(&self) -> String {
+        self.current.as_ref().map(|(_, label)| label.clone()).unwrap_or("Start".to_string())
+    }
+
+    /// Returns the failure of a test which has been stopped before it was finished.
+    pub(crate) fn aborted(&self) -> TestFailure {
+        TestFailure {
+            task: self.task(),
+            reason: ActorTestError::Aborted,
+            expected: None,
+            received: None,
+            steps: self.steps.clone()
+        }
+    }
+}
+
 /// This struct is used to store the testing state of an [TestActor]
 pub struct TestActor<S> {
     addr: Addr,
     tasks: VecDeque<TestTask<S>>,
-    test_state: TestActorState
+    test_state: TestActorState,
+    /// Time to wait for each response.
+    timeout: Duration,
+    progress: Arc<Mutex<TestProgress>>,
+    failure: Option<TestFailure>
 }
 
 /// Message used to reschedule messages to [TestActor].
 enum TestActorMessage {
-    RunNext
+    RunNext,
+    /// Sent after the timeout of the task with the given id has passed.
+    Timeout(u32)
 }
 
 impl<S: Send + 'static> TestActor<S> {
@@ -137,14 +243,30 @@ impl<S: Send + 'static> TestActor<S> {
     fn get_blanket_behavior() -> BehaviorBuilder<TestActor<S>> {
         BehaviorBuilder::new()
             .on_start(|_state: &mut TestActor<S>, ctx| {
+                // responses without handler are reported as dead letters, the test actor is always
+                // spawned on an actor system such that subscribing can not fail
+                let _ = ctx.subscribe::<DeadLetter>(DEAD_LETTERS_TOPIC);
                 // trigger self-loop for going through tasks
                 ctx.get_addr().tell(TestActorMessage::RunNext);
             })
+            .on_tell::<DeadLetter>(|msg, state, ctx| -> BehaviorAction<TestActor<S>> {
+                if ctx.name() != Some(msg.recipient.as_str()) {
+                    // dead letter of another actor
+                    return Behavior::keep();
+                }
+                // the test actor has no handler for the received message, so it is never expected
+                let expected = match &state.test_state {
+                    Ready => None,
+                    PendingResponse(resp) => resp.expected()
+                };
+                Err(Box::new(state.fail(ActorTestError::InvalidMessageOrder, expected, Some(msg.message_type))))
+            })
             .on_tell::<StateCheckMessage<S>>(|msg, state, ctx| -> BehaviorAction<TestActor<S>> {
+                let received = Some(std::any::type_name::<StateCheckMessage<S>>());
                 match &state.test_state {
                     Ready => {
-                        // panic!("Did not expect a check result in the current state");
-                        return Err(Box::new(ActorTestError::InvalidMessageOrder));
+                        // did not expect a check result in the current state
+                        return Err(Box::new(state.fail(ActorTestError::InvalidMessageOrder, None, received)));
                     }
                     PendingResponse(resp) => {
                         match resp {
@@ -156,10 +278,10 @@ impl<S: Send + 'static> TestActor<S> {
                                     }
                                     StateCheckMessage::Result(check_result) => {
                                         state.test_state = Ready;
-                                        if check_result == false {
-                                            // panic!("state check failed!");
-                                            return Err(Box::new(ActorTestError::StateCheckFailed));
+                                        if !check_result {
+                                            return Err(Box::new(state.fail(ActorTestError::StateCheckFailed, None, None)));
                                         }
+                                        state.log("passed".to_string());
                                     }
                                 }
 
@@ -167,8 +289,9 @@ impl<S: Send + 'static> TestActor<S> {
                                 ctx.get_addr().tell(TestActorMessage::RunNext);
                             }
                             _ => {
-                                // panic!("Did not expect a check result in the current state");
-                                return Err(Box::new(ActorTestError::InvalidMessageOrder));
+                                // did not expect a check result in the current state
+                                let expected = resp.expected();
+                                return Err(Box::new(state.fail(ActorTestError::InvalidMessageOrder, expected, received)));
                             }
                         }
                     }
@@ -176,31 +299,50 @@ impl<S: Send + 'static> TestActor<S> {
 
                 Behavior::keep()
             })
-            .on_tell::<TestActorMessage>(|_msg, state, ctx| -> BehaviorAction<TestActor<S>> {
+            .on_tell::<TestActorMessage>(|msg, state, ctx| -> BehaviorAction<TestActor<S>> {
+                if let TestActorMessage::Timeout(id) = msg {
+                    // the timeout is only relevant if the task it belongs to is still waiting for its response
+                    if let (Some(current), PendingResponse(resp)) = (state.current_id(), &state.test_state) {
+                        if current == id {
+                            let expected = resp.expected();
+                            return Err(Box::new(state.fail(ActorTestError::Timeout, expected, None)));
+                        }
+                    }
+                    return Behavior::keep();
+                }
+
                 // this handlers job is to run the given test tasks
                 if let Some(task) = state.tasks.pop_front() {
-                    println!("Current task: {:?}", &task);
+                    state.progress.lock().unwrap().current = Some((task.id(), format!("{:?}", &task)));
                     match task {
                         TestTask::Tell(msg, _id) => {
+                            state.log(format!("sent {}", msg.type_name()));
                             state.addr.send(msg);
                             ctx.get_addr().tell(TestActorMessage::RunNext);
                         },
-                        TestTask::Ask(mut msg, response, _id) => {
+                        TestTask::Ask(mut msg, response, id) => {
+                            state.log(format!("sent {}", msg.type_name()));
                             // fill in reply_to such that ask queries are responded to this actor
                             msg.sender = Some(ctx.get_addr());
                             state.test_state = TestActorState::PendingResponse(response);
 
                             // send tell message to actor
                             state.addr.send(msg);
+                            ctx.get_addr().tell_delayed(TestActorMessage::Timeout(id), state.timeout);
                         },
-                        TestTask::Check(check_fn, _id) => {
+                        TestTask::Check(check_fn, id) => {
+                            // the check is sent through the data lane on purpose, such that it sees the state
+                            // after all previously sent messages have been handled
                             state.addr.ask(StateCheckMessage::<S>::Check(check_fn), ctx.get_addr());
                             state.test_state = PendingResponse(ResponseDyn::Check);
+                            ctx.get_addr().tell_delayed(TestActorMessage::Timeout(id), state.timeout);
                         }
-                        TestTask::Expect(response, _id) => {
+                        TestTask::Expect(response, id) => {
                             state.test_state = TestActorState::PendingResponse(response);
+                            ctx.get_addr().tell_delayed(TestActorMessage::Timeout(id), state.timeout);
                         }
                         TestTask::Exit => {
+                            state.log("finished".to_string());
                             ctx.kill()
                         }
                     }
@@ -211,6 +353,87 @@ impl<S: Send + 'static> TestActor<S> {
             })
     }
 
+    /// Returns the id of the task which is currently run.
+    fn current_id(&self) -> Option<u32> {
+        self.progress.lock().unwrap().current.as_ref().map(|(id, _)| *id)
+    }
+
+    /// Returns the progress of this test actor.
+    pub(crate) fn progress(&self) -> Arc<Mutex<TestProgress>> {
+        self.progress.clone()
+    }
+
+    /// Appends the given outcome of the current task to the log of executed steps.
+    fn log(&mut self, outcome: String) {
+        let mut progress = self.progress.lock().unwrap();
+        let step = format!("{}: {}", progress.task(), outcome);
+        progress.steps.push(step);
+    }
+
+    /// Records the failure of the current task and returns the reason.
+    fn fail(&mut self, reason: ActorTestError, expected: Option<&'static str>, received: Option<&'static str>) -> ActorTestError {
+        let progress = self.progress.lock().unwrap();
+        self.failure = Some(TestFailure {
+            task: progress.task(),
+            reason,
+            expected,
+            received,
+            steps: progress.steps.clone()
+        });
+        reason
+    }
+
+    /// Checks the given response of type M, which has been sent with ask if ask is set, against
+    /// the response the current task waits for.
+    fn check_response<M: Any + Send>(&mut self, msg: M, ask: bool) -> Result<(), ActorTestError> {
+        let received = Some(std::any::type_name::<M>());
+        let resp = match std::mem::replace(&mut self.test_state, Ready) {
+            Ready => {
+                // did not expect any message
+                return Err(self.fail(ActorTestError::InvalidMessageOrder, None, received));
+            }
+            PendingResponse(resp) => resp
+        };
+        let expected = resp.expected();
+        match resp {
+            ResponseDyn::Ask(expected_type_id, _, criteria) if ask => {
+                if TypeId::of::<M>() != expected_type_id {
+                    return Err(self.fail(ActorTestError::InvalidMessageOrder, expected, received));
+                }
+                if !criteria(Message::without_sender(msg)) {
+                    return Err(self.fail(ActorTestError::CriteriaNotMet, expected, received));
+                }
+            }
+            ResponseDyn::Tell(expected_type_id, _, criteria) if !ask => {
+                if TypeId::of::<M>() != expected_type_id {
+                    return Err(self.fail(ActorTestError::InvalidMessageOrder, expected, received));
+                }
+                if !criteria(Message::without_sender(msg)) {
+                    return Err(self.fail(ActorTestError::CriteriaNotMet, expected, received));
+                }
+            }
+            _ => {
+                // either a tell instead of an ask message or vice versa or not a check result
+                return Err(self.fail(ActorTestError::InvalidMessageOrder, expected, received));
+            }
+        }
+        self.log(format!("received {}", std::any::type_name::<M>()));
+        Ok(())
+    }
+
+    /// Returns the result of the test once the test actor has exited with the given reason.
+    pub(crate) fn result(&self, exit_reason: ExitReason) -> Result<TestReport, TestFailure> {
+        let progress = self.progress.lock().unwrap();
+        if let ExitReason::Kill = exit_reason {
+            if self.failure.is_none() {
+                return Ok(TestReport {
+                    steps: progress.steps.clone()
+                });
+            }
+        }
+        Err(self.failure.clone().unwrap_or_else(|| progress.aborted()))
+    }
+
 }
 
 /// This builder is used to build a [TestActor].
@@ -219,7 +442,8 @@ pub struct ActorTestBuilder<S: Send + 'static> {
     addr: Addr,
     tasks: VecDeque<TestTask<S>>,
     test_state: TestActorState,
-    task_id_gen: u32
+    task_id_gen: u32,
+    timeout: Duration
 }
 
 /// This enum represents the possible message types an [Actor] can send.
@@ -239,7 +463,8 @@ impl<S: Send + 'static> ActorTestBuilder<S> {
             addr: addr,
             tasks: VecDeque::new(),
             test_state: TestActorState::Ready,
-            task_id_gen: 0
+            task_id_gen: 0,
+            timeout: DEFAULT_TIMEOUT
         }
     }
 
@@ -249,6 +474,14 @@ impl<S: Send + 'static> ActorTestBuilder<S> {
         self.task_id_gen -1
     }
 
+    /// Sets the time to wait for each response and state check result before the test fails with
+    /// [ActorTestError::Timeout]. Defaults to 5 seconds. The time is measured by the clock of the
+    /// actor system the test is spawned on.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// Adds the given check function to the test list. check_fn can immutably access the whole
     /// internal state of the actor to be tested.
     pub fn check(mut self, check_fn: fn(&S) -> bool) -> Self {
@@ -277,11 +510,11 @@ impl<S: Send + 'static> ActorTestBuilder<S> {
         let next_id = self.next_task_id();
 
         match &expected_response {
-            ResponseDyn::Ask(_, _) => {
+            ResponseDyn::Ask(_, _, _) => {
                 self.tasks.push_back(TestTask::Ask(msg, expected_response, next_id));
                 self.set_default_ask_response_behavior::<R>()
             }
-            ResponseDyn::Tell(_, _) => {
+            ResponseDyn::Tell(_, _, _) => {
                 self.tasks.push_back(TestTask::Ask(msg, expected_response, next_id));
                 self.set_default_tell_response_behavior::<R>()
             }
@@ -300,35 +533,8 @@ impl<S: Send + 'static> ActorTestBuilder<S> {
 
             self.behavior_builder = self.behavior_builder
                 .on_tell::<M>(|msg, state, ctx| -> BehaviorAction<TestActor<S>> {
+                    state.check_response(msg, false)?;
 
-                    match &state.test_state {
-                        Ready => {
-                            // panic!("did not expect a tell message");
-                            return Err(Box::new(ActorTestError::InvalidMessageOrder));
-                        }
-                        PendingResponse(resp) => {
-                            match resp {
-                                ResponseDyn::Ask(_, _) => {
-                                    // panic!("did not expect an ask message");
-                                    return Err(Box::new(ActorTestError::InvalidMessageOrder));
-                                }
-                                ResponseDyn::Tell(expected_type_id, criteria) => {
-                                    if TypeId::of::<M>() == *expected_type_id {
-                                        if criteria(Message::without_sender(msg)) == false {
-                                            return Err(Box::new(ActorTestError::CriteriaNotMet));
-                                            // panic!("tell message did not pass criteria check!");
-                                        }
-                                    }
-                                }
-                                Check => {
-                                    // panic!("did not expect a check message")
-                                    return Err(Box::new(ActorTestError::InvalidMessageOrder));
-                                }
-                            }
-                        }
-                    }
-
-                    state.test_state = TestActorState::Ready;
                     // continue working on tasks
                     ctx.get_addr().tell(TestActorMessage::RunNext);
                     Behavior::keep()
@@ -345,33 +551,8 @@ impl<S: Send + 'static> ActorTestBuilder<S> {
 
             self.behavior_builder = self.behavior_builder
                 .on_ask::<M>(|msg, state, _addr, ctx| -> BehaviorAction<TestActor<S>> {
+                    state.check_response(msg, true)?;
 
-                    match &state.test_state {
-                        Ready => {
-                            // panic!("did not expect an ask message");
-                            return Err(Box::new(ActorTestError::InvalidMessageOrder));
-                        }
-                        PendingResponse(resp) => {
-                            match resp {
-                                ResponseDyn::Ask(expected_type_id, criteria) => {
-                                    if TypeId::of::<M>() == *expected_type_id {
-                                        if criteria(Message::without_sender(msg)) == false {
-                                            // panic!("ask message did not pass criteria check!");
-                                            return Err(Box::new(ActorTestError::CriteriaNotMet));
-                                        }
-                                    }
-                                }
-                                ResponseDyn::Tell(_, _) => {
-                                    // panic!("did not expect a tell message");
-                                    return Err(Box::new(ActorTestError::InvalidMessageOrder));
-                                }
-                                Check => {
-                                    // panic!("did not expect a check message")
-                                    return Err(Box::new(ActorTestError::InvalidMessageOrder));
-                                }
-                            }
-                        }
-                    }
                     // continue working on tasks
                     ctx.get_addr().tell(TestActorMessage::RunNext);
                     Behavior::keep()
@@ -404,7 +585,10 @@ impl<S: Send + 'static> ActorTestBuilder<S> {
         let state = TestActor {
             addr: self.addr,
             tasks: self.tasks,
-            test_state: TestActorState::Ready
+            test_state: TestActorState::Ready,
+            timeout: self.timeout,
+            progress: Arc::new(Mutex::new(TestProgress::default())),
+            failure: None
         };
 
         Actor::new(state, self.behavior_builder.build(), MailboxType::Unbounded)
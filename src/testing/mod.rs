@@ -36,8 +36,9 @@
 //!         .tell(ActorManageMessage::Kill)
 //!         .build();
 //!
-//!     let test_res = sys.spawn_test(test_actor).await;
-//!     assert_eq!(test_res, true);
+//!     // on failure, the error names the failed task and lists all steps executed before
+//!     let report = sys.spawn_test(test_actor).await.unwrap();
+//!     println!("{:?}", report.steps);
 //!
 //!     // start actor system to run actors
 //!     sys.start().await;
@@ -45,7 +46,12 @@
 //! }
 //! ```
 //!
+//! Messages which are sent by an actor under test, e.g. replies or messages to collaborators, can
+//! be asserted with a [TestProbe], whose [Addr](crate::Addr) is handed to the actor.
+//!
 
 mod actor_test;
-pub use actor_test::{TestActor, ActorTestBuilder, Response, MessageType};
+mod test_probe;
+pub use actor_test::{TestActor, ActorTestBuilder, Response, MessageType, ActorTestError, TestReport, TestFailure};
+pub use test_probe::{TestProbe, ProbeError};
 
@@ -0,0 +1,157 @@
+use std::any::Any;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::time::timeout;
+
+use crate::actor::Mailbox;
+use crate::{Addr, Message};
+
+/// Error which is returned if a [TestProbe] did not receive the expected messages.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ProbeError {
+    #[error("No message received within {0:?}")]
+    Timeout(Duration),
+    #[error("Expected a message of type {expected} but received a message of type {received}")]
+    UnexpectedType {
+        expected: &'static str,
+        received: &'static str
+    },
+    #[error("Received message of type {0} does not match the given criteria")]
+    CriteriaNotMet(&'static str),
+    #[error("Expected no message but received a message of type {0}")]
+    UnexpectedMessage(&'static str),
+    #[error("The last received message has not been sent with ask")]
+    NoSender
+}
+
+/// A [TestProbe] owns a mailbox whose [Addr] can be handed to the actors under test, e.g. as
+/// reply_to address or as address of a collaborator, such that the messages they send can be
+/// asserted from ordinary `#[tokio::test]` code without building a test behavior.
+///
+/// Example:
+/// ```
+/// use std::time::Duration;
+/// use aector::actor::{Actor, MailboxType};
+/// use aector::actor_system::ActorSystem;
+/// use aector::behavior::{Behavior, BehaviorBuilder};
+/// use aector::testing::TestProbe;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let behavior = BehaviorBuilder::new()
+///     .on_ask::<u32>(|msg, _state, reply_to, _ctx| {
+///         reply_to.tell(msg * 2);
+///         Behavior::keep()
+///     })
+///     .build();
+/// let sys = ActorSystem::new();
+/// let actor = sys.spawn(Actor::new((), behavior, MailboxType::Unbounded), "doubler".to_string()).unwrap();
+///
+/// let mut probe = TestProbe::new();
+/// actor.get_addr().ask(21u32, probe.addr());
+/// let reply = probe.expect_msg::<u32>(Duration::from_secs(1)).await.unwrap();
+/// assert_eq!(reply, 42);
+/// probe.expect_no_msg(Duration::from_millis(10)).await.unwrap();
+/// # }
+/// ```
+pub struct TestProbe {
+    mailbox: Mailbox,
+    /// Sender of the last received message, [Option::None] if it has been sent with tell.
+    last_sender: Option<Addr>
+}
+
+impl TestProbe {
+    /// Creates a new probe with an unbounded mailbox.
+    pub fn new() -> Self {
+        Self {
+            mailbox: Mailbox::unbounded(),
+            last_sender: None
+        }
+    }
+
+    /// Returns the [Addr] of this probe.
+    pub fn addr(&self) -> Addr {
+        self.mailbox.get_addr()
+    }
+
+    /// Waits at most for the given timeout for the next message.
+    async fn recv(&mut self, timeout_duration: Duration) -> Result<Message, ProbeError> {
+        // the mailbox holds an address itself, such that it is never closed
+        match timeout(timeout_duration, self.mailbox.recv()).await {
+            Ok(Some(msg)) => {
+                self.last_sender = msg.sender.clone();
+                Ok(msg)
+            }
+            _ => Err(ProbeError::Timeout(timeout_duration))
+        }
+    }
+
+    /// Waits at most for the given timeout for the next message and returns it if it is of type M.
+    pub async fn expect_msg<M: Any + Send>(&mut self, timeout: Duration) -> Result<M, ProbeError> {
+        let msg = self.recv(timeout).await?;
+        if !msg.is::<M>() {
+            return Err(ProbeError::UnexpectedType {
+                expected: std::any::type_name::<M>(),
+                received: msg.type_name()
+            });
+        }
+        Ok(*msg.downcast::<M>())
+    }
+
+    /// Waits at most for the given timeout for the next message and returns it if it is of type M
+    /// and fulfills the given criteria.
+    pub async fn expect_msg_matching<M: Any + Send, F: FnOnce(&M) -> bool>(&mut self, timeout: Duration, criteria: F) -> Result<M, ProbeError> {
+        let msg = self.expect_msg::<M>(timeout).await?;
+        if !criteria(&msg) {
+            return Err(ProbeError::CriteriaNotMet(std::any::type_name::<M>()));
+        }
+        Ok(msg)
+    }
+
+    /// Asserts that no message is received within the given duration.
+    pub async fn expect_no_msg(&mut self, duration: Duration) -> Result<(), ProbeError> {
+        match self.recv(duration).await {
+            Ok(msg) => Err(ProbeError::UnexpectedMessage(msg.type_name())),
+            Err(_) => Ok(())
+        }
+    }
+
+    /// Waits at most for the given timeout in total for the next n messages of any type. Their
+    /// content can be inspected with [Message.downcast_ref()](crate::Message#method.downcast_ref).
+    pub async fn receive_n(&mut self, n: usize, timeout_duration: Duration) -> Result<Vec<Message>, ProbeError> {
+        let mut received = Vec::with_capacity(n);
+        let res = timeout(timeout_duration, async {
+            while received.len() < n {
+                // the mailbox is never closed, see recv()
+                if let Some(msg) = self.mailbox.recv().await {
+                    self.last_sender = msg.sender.clone();
+                    received.push(msg);
+                }
+            }
+        }).await;
+        match res {
+            Ok(()) => Ok(received),
+            Err(_) => Err(ProbeError::Timeout(timeout_duration))
+        }
+    }
+
+    /// Sends the given message to the sender of the last received message, which has to be sent
+    /// with ask.
+    pub fn reply_to_last<M: Any + Send>(&self, msg: M) -> Result<(), ProbeError> {
+        match &self.last_sender {
+            None => Err(ProbeError::NoSender),
+            Some(sender) => {
+                sender.tell(msg);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Default for TestProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,175 @@
+//! Detection of slow handlers and stalled actors. A [Watchdog] is started with
+//! [ActorSystem.start_watchdog()](crate::actor_system::ActorSystem#method.start_watchdog) and reports
+//! its findings as warnings via [tracing].
+//!
+//! Example:
+//! ```
+//! use std::time::Duration;
+//! use aector::actor_system::ActorSystem;
+//! use aector::watchdog::Watchdog;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let actor_sys = ActorSystem::new();
+//! let watchdog = Watchdog::new()
+//!     .with_slow_handler_threshold(Duration::from_millis(100))
+//!     .with_stall_timeout(Duration::from_secs(5))
+//!     .with_supervision(true);
+//! actor_sys.start_watchdog(watchdog).unwrap();
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::warn;
+
+use crate::actor::ActorId;
+use crate::actor_system::ActorSystem;
+use crate::address::Addr;
+
+/// Configuration of the watchdog of an [ActorSystem]. A handler is considered slow if a single
+/// invocation takes longer than the slow handler threshold. An actor is considered stalled if its
+/// mailbox is not empty and it has not handled any message for the stall timeout. If supervision
+/// is enabled, stalled actors exit as if their handler had failed once they get to handle their next
+/// message, such that their [SupervisionStrategy](crate::supervision::SupervisionStrategy) decides
+/// what happens next. The strategy can recognize stalled actors by their
+/// [Actor.watchdog_error()](crate::actor::Actor#method.watchdog_error).
+#[derive(Clone, Debug)]
+pub struct Watchdog {
+    slow_handler_threshold: Option<Duration>,
+    stall_timeout: Option<Duration>,
+    supervise_stalled: bool,
+    interval: Duration
+}
+
+impl Watchdog {
+    /// Creates a watchdog which neither checks for slow handlers nor for stalled actors. By default
+    /// actors are checked every second.
+    pub fn new() -> Self {
+        Self {
+            slow_handler_threshold: None,
+            stall_timeout: None,
+            supervise_stalled: false,
+            interval: Duration::from_secs(1)
+        }
+    }
+
+    /// Sets the duration after which a single handler invocation is reported as slow.
+    pub fn with_slow_handler_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_handler_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets the duration for which an actor with a non empty mailbox may not handle any message
+    /// before it is reported as stalled.
+    pub fn with_stall_timeout(mut self, timeout: Duration) -> Self {
+        self.stall_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether stalled actors are made to exit with [ExitReason::Error](crate::actor::ExitReason::Error).
+    pub fn with_supervision(mut self, supervise_stalled: bool) -> Self {
+        self.supervise_stalled = supervise_stalled;
+        self
+    }
+
+    /// Sets the interval in which the actors are checked, which is at least one millisecond.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(Duration::from_millis(1));
+        self
+    }
+
+    pub(crate) fn slow_handler_threshold(&self) -> Option<Duration> {
+        self.slow_handler_threshold
+    }
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error with which an actor exits if it has been detected as stalled by the [Watchdog].
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum WatchdogError {
+    #[error("Actor did not handle any message for {0:?}")]
+    Stalled(Duration)
+}
+
+/// System message sent to stalled actors if supervision is enabled.
+pub(crate) struct StallDetected(pub(crate) Duration);
+
+/// Progress of an actor as observed by the watchdog.
+struct Progress {
+    handled: u64,
+    since: Instant,
+    reported: bool,
+    /// Start of the handler invocation which has already been reported as slow while running.
+    reported_running: Option<u64>
+}
+
+/// Periodically checks all registered actors of the given actor system until it is stopped.
+pub(crate) async fn run(sys: Arc<ActorSystem>, watchdog: Watchdog) {
+    let mut shutdown = sys.subscribe_shutdown();
+    let mut check_interval = interval(watchdog.interval);
+    check_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut progress: HashMap<ActorId, Progress> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            _ = shutdown.wait_for(|stopped| *stopped) => {
+                return;
+            }
+            _ = check_interval.tick() => {}
+        }
+
+        let actors = sys.watched_actors();
+        progress.retain(|id, _| actors.iter().any(|(_, actor_id, _)| actor_id == id));
+        for (name, id, addr) in actors {
+            let entry = progress.entry(id).or_insert_with(|| Progress {
+                handled: addr.stats().handled(),
+                since: Instant::now(),
+                reported: false,
+                reported_running: None
+            });
+            check(&watchdog, &name, &addr, entry);
+        }
+    }
+}
+
+fn check(watchdog: &Watchdog, name: &str, addr: &Addr, progress: &mut Progress) {
+    let stats = addr.stats();
+
+    // handlers which never return can only be detected while they are running
+    if let (Some(threshold), Some((started, running_for))) = (watchdog.slow_handler_threshold, stats.handling_since()) {
+        if running_for > threshold && progress.reported_running != Some(started) {
+            warn!("Handler of actor {} has been running for {}ms", name, running_for.as_millis());
+            progress.reported_running = Some(started);
+        }
+    }
+
+    let handled = stats.handled();
+    if handled != progress.handled || stats.queued() == 0 {
+        progress.handled = handled;
+        progress.since = Instant::now();
+        progress.reported = false;
+        return;
+    }
+
+    if let Some(timeout) = watchdog.stall_timeout {
+        let stalled_for = progress.since.elapsed();
+        if stalled_for > timeout && !progress.reported {
+            warn!("Actor {} has not handled any of its {} waiting messages for {}ms", name, stats.queued(), stalled_for.as_millis());
+            progress.reported = true;
+            if watchdog.supervise_stalled {
+                addr.tell(StallDetected(stalled_for));
+            }
+        }
+    }
+}
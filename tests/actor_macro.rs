@@ -0,0 +1,109 @@
+#![cfg(feature = "macros")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aector::actor::{Actor, ActorContext, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{ActorManageMessage, Behavior, BehaviorAction};
+use aector::testing::TestProbe;
+use aector::Addr;
+
+struct Add(i32);
+struct Get;
+
+/// Same type as [Add], which the macro can not tell apart from its tokens.
+type Increment = Add;
+
+/// Counter which reports to the probe whose address is part of its state once it is stopped.
+struct Counter {
+    count: i32,
+    probe: Addr
+}
+
+#[aector::actor]
+impl Counter {
+    #[tell]
+    fn add(&mut self, msg: Add, _ctx: &mut ActorContext) -> BehaviorAction<Counter> {
+        self.count += msg.0;
+        Behavior::keep()
+    }
+
+    #[ask]
+    fn get(&mut self, _msg: Get, reply_to: Addr, _ctx: &mut ActorContext) -> BehaviorAction<Counter> {
+        reply_to.tell(self.count);
+        Behavior::keep()
+    }
+
+    #[on_stop]
+    fn stop(&mut self, _ctx: &mut ActorContext) {
+        self.probe.tell("stopped");
+    }
+}
+
+struct Duplicated;
+
+#[aector::actor]
+impl Duplicated {
+    #[tell]
+    fn add(&mut self, _msg: Add, _ctx: &mut ActorContext) -> BehaviorAction<Duplicated> {
+        Behavior::keep()
+    }
+
+    #[tell]
+    fn increment(&mut self, _msg: Increment, _ctx: &mut ActorContext) -> BehaviorAction<Duplicated> {
+        Behavior::keep()
+    }
+}
+
+async fn wait_until_removed(sys: &Arc<ActorSystem>, name: &str) -> bool {
+    for _ in 0..100 {
+        if sys.query(name).is_none() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+/// Checks that the given counter adds, answers and reports being stopped once it is killed.
+async fn check_counter(actor: Actor<Counter>, probe: &mut TestProbe, name: &str) {
+    let sys = ActorSystem::new();
+    let addr = actor.get_addr();
+    sys.spawn(actor, name.to_string()).unwrap();
+
+    addr.tell(Add(1));
+    addr.tell(Add(2));
+    addr.ask(Get, probe.addr());
+    assert_eq!(probe.expect_msg::<i32>(Duration::from_secs(1)).await.unwrap(), 3);
+
+    addr.tell(ActorManageMessage::Kill);
+    assert_eq!(probe.expect_msg::<&str>(Duration::from_secs(1)).await.unwrap(), "stopped");
+    assert!(wait_until_removed(&sys, name).await);
+}
+
+#[tokio::test]
+async fn into_actor_uses_generated_behavior() {
+    let mut probe = TestProbe::new();
+    let actor = Counter { count: 0, probe: probe.addr() }.into_actor(MailboxType::Unbounded);
+    check_counter(actor, &mut probe, "counter").await;
+}
+
+#[tokio::test]
+async fn generated_behavior_routes_messages_and_actions() {
+    let mut probe = TestProbe::new();
+    let actor = Actor::new(Counter { count: 0, probe: probe.addr() }, Counter::behavior(), MailboxType::Unbounded);
+    check_counter(actor, &mut probe, "counter").await;
+}
+
+#[test]
+#[should_panic(expected = "#[actor] methods `add` and `increment` both define a tell handler for message type")]
+fn duplicate_handlers_written_differently_panic_naming_the_methods() {
+    Duplicated::behavior();
+}
+
+#[test]
+fn duplicate_handlers_do_not_compile() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use aector::actor::{Actor, MailboxKind, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{Behavior, BehaviorAction, BehaviorBuilder};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stop_aborts_actors_after_grace_period() {
+    let behavior = BehaviorBuilder::new()
+        .on_tell::<u32>(|_msg, _state: &mut (), _ctx| -> BehaviorAction<()> {
+            // handler which does not return in time
+            std::thread::sleep(Duration::from_secs(2));
+            Behavior::keep()
+        })
+        .build();
+    let sys = ActorSystem::new();
+    let actor = sys.spawn(Actor::new((), behavior, MailboxType::Unbounded), "stuck".to_string()).unwrap();
+    sys.spawn(Actor::new((), BehaviorBuilder::new().build(), MailboxType::Unbounded), "idle".to_string()).unwrap();
+    actor.get_addr().tell(1u32);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    sys.stop_with_grace_period(Duration::from_millis(100));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    // the idle actor stops right away, the stuck one is still handling its message
+    assert_eq!(sys.list().len(), 1);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(sys.list().is_empty());
+}
+
+#[tokio::test]
+async fn list_reports_mailbox_types() {
+    let sys = ActorSystem::new();
+    let mailbox_types = [
+        ("bounded", MailboxType::Bounded(8)),
+        ("priority", MailboxType::Priority(|_msg| 0)),
+        ("unbounded", MailboxType::Unbounded)
+    ];
+    for (name, mailbox_type) in mailbox_types {
+        sys.spawn(Actor::new((), BehaviorBuilder::new().build(), mailbox_type), name.to_string()).unwrap();
+    }
+
+    let infos = sys.list();
+    assert_eq!(infos[0].mailbox_type, MailboxKind::Bounded(8));
+    assert_eq!(infos[0].mailbox_capacity, Some(8));
+    assert_eq!(infos[1].mailbox_type, MailboxKind::Priority);
+    assert_eq!(infos[1].mailbox_capacity, None);
+    assert_eq!(infos[2].mailbox_type, MailboxKind::Unbounded);
+    assert_eq!(infos[2].mailbox_capacity, None);
+}
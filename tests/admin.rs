@@ -0,0 +1,104 @@
+#![cfg(all(feature = "admin", unix))]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::UnixStream;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+
+use aector::actor::{Actor, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::Addr;
+use aector::admin::AdminConsole;
+use aector::behavior::{Behavior, BehaviorAction, BehaviorBuilder};
+use aector::testing::TestProbe;
+
+#[derive(Deserialize)]
+struct Deposit {
+    amount: u32
+}
+
+/// Account which reports every deposit to the probe whose address is the state.
+fn account(probe: Addr) -> Actor<Addr> {
+    let behavior = BehaviorBuilder::new()
+        .on_tell::<Deposit>(|msg, probe: &mut Addr, _ctx| -> BehaviorAction<Addr> {
+            probe.tell(msg.amount);
+            Behavior::keep()
+        })
+        .build();
+    Actor::new(probe, behavior, MailboxType::Unbounded)
+}
+
+/// Sends a command to the console and returns its output lines including the terminating line.
+async fn command(write: &mut OwnedWriteHalf, lines: &mut Lines<BufReader<OwnedReadHalf>>, command: &str) -> Vec<String> {
+    write.write_all(format!("{}\n", command).as_bytes()).await.unwrap();
+    let mut output = Vec::new();
+    loop {
+        let line = tokio::time::timeout(Duration::from_secs(1), lines.next_line()).await
+            .expect("console did not answer")
+            .unwrap()
+            .expect("console closed the connection");
+        let done = line == "ok" || line.starts_with("error:");
+        output.push(line);
+        if done {
+            return output;
+        }
+    }
+}
+
+async fn wait_until_removed(sys: &Arc<ActorSystem>, name: &str) -> bool {
+    for _ in 0..100 {
+        if sys.query(name).is_none() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn console_answers_commands() {
+    let path = std::env::temp_dir().join(format!("aector-admin-{}.sock", std::process::id()));
+    let sys = ActorSystem::new();
+    let mut probe = TestProbe::new();
+    sys.spawn(account(probe.addr()), "account".to_string()).unwrap();
+    sys.start_admin(AdminConsole::new(&path).register::<Deposit>("Deposit")).unwrap();
+
+    let (read, mut write) = UnixStream::connect(&path).await.unwrap().into_split();
+    let mut lines = BufReader::new(read).lines();
+
+    let list = command(&mut write, &mut lines, "list").await;
+    assert_eq!(list.len(), 2);
+    assert!(list[0].starts_with("account id="));
+    assert_eq!(list[1], "ok");
+
+    let stats = command(&mut write, &mut lines, "stats account").await;
+    assert_eq!(stats[0], "name: account");
+    assert!(stats.contains(&"mailbox: 0/unbounded".to_string()));
+    assert_eq!(stats.last().unwrap(), "ok");
+
+    let missing = command(&mut write, &mut lines, "stats unknown").await;
+    assert_eq!(missing, vec!["error: no actor named unknown"]);
+
+    let tell = command(&mut write, &mut lines, r#"tell account {"Deposit": {"amount": 10}}"#).await;
+    assert_eq!(tell, vec!["ok"]);
+    assert_eq!(probe.expect_msg::<u32>(Duration::from_secs(1)).await.unwrap(), 10);
+
+    let unregistered = command(&mut write, &mut lines, r#"tell account {"Withdraw": {"amount": 10}}"#).await;
+    assert_eq!(unregistered, vec!["error: unknown message type Withdraw"]);
+
+    let unknown = command(&mut write, &mut lines, "frobnicate account").await;
+    assert_eq!(unknown, vec!["error: unknown command frobnicate"]);
+
+    let kill = command(&mut write, &mut lines, "kill account").await;
+    assert_eq!(kill, vec!["ok"]);
+    assert!(wait_until_removed(&sys, "account").await);
+
+    let list = command(&mut write, &mut lines, "list").await;
+    assert_eq!(list, vec!["ok"]);
+
+    sys.stop();
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use aector::Addr;
+use aector::actor::{Actor, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{ActorManageMessage, Behavior, BehaviorAction, BehaviorBuilder, BehaviorError, ConflictRule};
+use aector::testing::TestProbe;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Waits until the actor with the given name has been removed from the actor system.
+async fn wait_until_removed(sys: &Arc<ActorSystem>, name: &str) -> bool {
+    for _ in 0..100 {
+        if sys.query(name).is_none() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn short_circuiting_interceptor_does_not_block_kill() {
+    let handled = Arc::new(AtomicUsize::new(0));
+    let behavior = BehaviorBuilder::new()
+        .on_tell::<u32>(|_msg, handled: &mut Arc<AtomicUsize>, _ctx| -> BehaviorAction<Arc<AtomicUsize>> {
+            handled.fetch_add(1, Ordering::SeqCst);
+            Behavior::keep()
+        })
+        // rejects every message without passing it on
+        .intercept(|_meta, _state, _ctx, _next| Behavior::keep())
+        .build();
+
+    let sys = ActorSystem::new();
+    let actor = sys.spawn(Actor::new(handled.clone(), behavior, MailboxType::Unbounded), "guarded".to_string()).unwrap();
+    let addr = actor.get_addr();
+
+    addr.tell(1u32);
+    addr.tell(ActorManageMessage::Kill);
+    assert!(wait_until_removed(&sys, "guarded").await);
+    assert_eq!(handled.load(Ordering::SeqCst), 0);
+}
+
+/// Behavior whose u32 handler reports "left" to the probe whose address is the state.
+fn left() -> BehaviorBuilder<Addr> {
+    BehaviorBuilder::new()
+        .on_tell::<u32>(|_msg, probe: &mut Addr, _ctx| -> BehaviorAction<Addr> {
+            probe.tell("left");
+            Behavior::keep()
+        })
+        .enable_state_checks()
+}
+
+/// Behavior whose u32 handler reports "right" to the probe whose address is the state.
+fn right() -> BehaviorBuilder<Addr> {
+    BehaviorBuilder::new()
+        .on_tell::<u32>(|_msg, probe: &mut Addr, _ctx| -> BehaviorAction<Addr> {
+            probe.tell("right");
+            Behavior::keep()
+        })
+        .enable_state_checks()
+}
+
+/// Spawns an actor with the given behavior, sends it a u32 and returns what its handler reported.
+async fn run_u32_handler(behavior: Behavior<Addr>) -> &'static str {
+    let mut probe = TestProbe::new();
+    let sys = ActorSystem::new();
+    let actor = sys.spawn_anonymous(Actor::new(probe.addr(), behavior, MailboxType::Unbounded));
+    actor.get_addr().tell(1u32);
+    probe.expect_msg::<&'static str>(TIMEOUT).await.unwrap()
+}
+
+#[tokio::test]
+async fn merge_conflict_rules() {
+    let res = left().build().merge(right().build(), ConflictRule::Error);
+    assert!(matches!(res, Err(BehaviorError::DuplicateTellHandler(_))));
+    let res = left().merge(right(), ConflictRule::Error);
+    assert!(matches!(res, Err(BehaviorError::DuplicateTellHandler(_))));
+
+    let merged = left().build().merge(right().build(), ConflictRule::PreferLeft).unwrap();
+    assert_eq!(run_u32_handler(merged).await, "left");
+
+    let merged = left().build().merge(right().build(), ConflictRule::PreferRight).unwrap();
+    assert_eq!(run_u32_handler(merged).await, "right");
+
+    let merged = left().build().or_else(right().build());
+    assert_eq!(run_u32_handler(merged).await, "left");
+
+    let with_start = || BehaviorBuilder::<Addr>::new().on_start(|_state, _ctx| {});
+    let res = with_start().merge(with_start(), ConflictRule::Error);
+    assert!(matches!(res, Err(BehaviorError::DuplicateAction("on_start"))));
+}
+
+#[tokio::test]
+async fn merge_never_conflicts_on_default_handlers() {
+    // both behaviors have handlers for ActorManageMessage and state checks
+    let health = BehaviorBuilder::<Addr>::new()
+        .on_tell::<String>(|_msg, _probe, _ctx| -> BehaviorAction<Addr> {
+            Behavior::keep()
+        })
+        .enable_state_checks()
+        .build();
+    let merged = left().build().merge(health, ConflictRule::Error).unwrap();
+    assert_eq!(run_u32_handler(merged).await, "left");
+}
+
+#[tokio::test]
+async fn merged_interceptors_only_wrap_their_own_handlers() {
+    // partial behavior of a library which rejects every message sent to its own handlers
+    let health = BehaviorBuilder::new()
+        .on_tell::<String>(|_msg, probe: &mut Addr, _ctx| -> BehaviorAction<Addr> {
+            probe.tell("health");
+            Behavior::keep()
+        })
+        .intercept(|_meta, _state, _ctx, _next| Behavior::keep())
+        .build();
+    let merged = left().build().or_else(health);
+
+    let mut probe = TestProbe::new();
+    let sys = ActorSystem::new();
+    let actor = sys.spawn_anonymous(Actor::new(probe.addr(), merged, MailboxType::Unbounded));
+    actor.get_addr().tell("ping".to_string());
+    actor.get_addr().tell(1u32);
+    assert_eq!(probe.expect_msg::<&'static str>(TIMEOUT).await.unwrap(), "left");
+    probe.expect_no_msg(Duration::from_millis(50)).await.unwrap();
+}
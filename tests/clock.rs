@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use aector::Addr;
+use aector::actor::{Actor, Backup, ExitReason, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{Behavior, BehaviorAction, BehaviorBuilder};
+use aector::clock::Clock;
+use aector::supervision::{SuperVisionAction, SupervisionStrategy};
+use aector::testing::TestProbe;
+
+/// Restarts the supervised actor after 50ms.
+struct RestartAfter50ms;
+
+impl SupervisionStrategy<Addr> for RestartAfter50ms {
+    fn apply(&mut self, _exit_reason: ExitReason, _backup: &Backup<Addr>, _actor: &mut Actor<Addr>) -> SuperVisionAction {
+        SuperVisionAction::RestartDelayed(Duration::from_millis(50))
+    }
+}
+
+async fn expect_msgs(probe: &mut TestProbe, expected: &[&str]) {
+    let received = probe.receive_n(expected.len(), Duration::from_secs(1)).await.unwrap();
+    let received: Vec<&str> = received.iter().map(|msg| *msg.downcast_ref::<&'static str>().unwrap()).collect();
+    assert_eq!(received, expected);
+    probe.expect_no_msg(Duration::from_millis(20)).await.unwrap();
+}
+
+#[tokio::test]
+async fn delays_fire_in_deadline_order_once_the_manual_clock_is_advanced() {
+    let sys = ActorSystem::with_clock(Clock::manual());
+    let mut probe = TestProbe::new();
+
+    // forwards told messages to the probe, answers asks and schedules functions
+    let forwarder = BehaviorBuilder::new()
+        .on_tell::<&'static str>(|msg, probe: &mut Addr, _ctx| -> BehaviorAction<Addr> {
+            probe.tell(msg);
+            Behavior::keep()
+        })
+        .on_ask::<&'static str>(|_msg, _probe, reply_to, _ctx| -> BehaviorAction<Addr> {
+            reply_to.tell("ask");
+            Behavior::keep()
+        })
+        .on_tell::<u64>(|millis, probe, ctx| -> BehaviorAction<Addr> {
+            let probe = probe.clone();
+            ctx.run_delayed(Box::new(move || probe.tell("run")), Duration::from_millis(millis));
+            Behavior::keep()
+        })
+        .build();
+    let forwarder = sys.spawn(Actor::new(probe.addr(), forwarder, MailboxType::Unbounded), "forwarder".to_string()).unwrap();
+
+    // fails on every message and is restarted after a delay
+    let failing = BehaviorBuilder::new()
+        .on_start(|probe: &mut Addr, _ctx| {
+            probe.tell("started");
+        })
+        .on_tell::<bool>(|_msg, _probe, _ctx| -> BehaviorAction<Addr> {
+            Err("failed".into())
+        })
+        .build();
+    let failing = Actor::new(probe.addr(), failing, MailboxType::Unbounded);
+    let failing = sys.spawn_with_supervision(failing, Box::new(RestartAfter50ms), "failing".to_string()).unwrap();
+    expect_msgs(&mut probe, &["started"]).await;
+
+    // deadlines: run at 5ms, a at 10ms, b at 20ms, ask at 25ms, c at 30ms and the restart at 50ms
+    let addr = forwarder.get_addr();
+    addr.tell_delayed("c", Duration::from_millis(30));
+    addr.tell_delayed("a", Duration::from_millis(10));
+    addr.ask_delayed("ask", probe.addr(), Duration::from_millis(25));
+    addr.tell_delayed("b", Duration::from_millis(20));
+    addr.tell(5u64);
+    failing.get_addr().tell(true);
+    // nothing fires in real time
+    probe.expect_no_msg(Duration::from_millis(50)).await.unwrap();
+
+    sys.advance(Duration::from_millis(4)).unwrap();
+    probe.expect_no_msg(Duration::from_millis(20)).await.unwrap();
+
+    sys.advance(Duration::from_millis(31)).unwrap();
+    expect_msgs(&mut probe, &["run", "a", "b", "ask", "c"]).await;
+
+    sys.advance(Duration::from_millis(10)).unwrap();
+    probe.expect_no_msg(Duration::from_millis(20)).await.unwrap();
+
+    sys.advance(Duration::from_millis(10)).unwrap();
+    expect_msgs(&mut probe, &["started"]).await;
+    assert_eq!(sys.clock().now(), Duration::from_millis(55));
+}
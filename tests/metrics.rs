@@ -0,0 +1,80 @@
+#![cfg(feature = "metrics")]
+
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use aector::actor::{Actor, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{Behavior, BehaviorAction, BehaviorBuilder};
+use aector::routing::{Resizer, RoutingLogic};
+use aector::supervision::strategies::SimpleRestartStrategy;
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn metrics_endpoint_serves_actor_metrics() {
+    let behavior = BehaviorBuilder::new()
+        .on_tell::<u32>(|_msg, _state: &mut (), _ctx| -> BehaviorAction<()> {
+            Behavior::keep()
+        })
+        .on_tell::<&'static str>(|msg, _state, _ctx| -> BehaviorAction<()> {
+            Err(msg.into())
+        })
+        .build();
+    let sys = ActorSystem::new();
+    let actor = Actor::new((), behavior, MailboxType::Unbounded);
+    let actor = sys.spawn_with_supervision(actor, SimpleRestartStrategy::new(), "worker".to_string()).unwrap();
+    let addr = actor.get_addr();
+    addr.tell(1u32);
+    addr.tell(2u32);
+    addr.tell("fail");
+    // there is no handler for bool, so the message ends up as a dead letter
+    addr.tell(true);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let endpoint = sys.serve_metrics(0).await.unwrap();
+    assert_ne!(endpoint.port(), 0);
+    let response = get(endpoint, "/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert!(response.contains("aector_messages_handled_total{actor=\"worker\",message_type=\"u32\"} 2\n"), "{}", response);
+    assert!(response.contains("aector_actor_restarts_total{actor=\"worker\"} 1\n"), "{}", response);
+    assert!(response.contains("aector_dead_letters_total 1\n"), "{}", response);
+
+    let response = get(endpoint, "/unknown").await;
+    assert!(response.starts_with("HTTP/1.1 404 Not Found"), "{}", response);
+}
+
+fn worker() -> Actor<()> {
+    let behavior = BehaviorBuilder::new()
+        .on_tell::<u32>(|_msg, _state: &mut (), _ctx| -> BehaviorAction<()> {
+            Behavior::keep()
+        })
+        .build();
+    Actor::new((), behavior, MailboxType::Unbounded)
+}
+
+#[tokio::test]
+async fn metrics_of_pools_are_labelled_with_pool_name() {
+    let sys = ActorSystem::new();
+    let pool = sys.spawn_pool(worker, 3, RoutingLogic::RoundRobin, "workers".to_string()).unwrap();
+    let resizable = sys.spawn_resizable_pool(worker, Resizer::new(1, 2), RoutingLogic::RoundRobin, "resizable".to_string()).unwrap();
+    for n in 0..6u32 {
+        pool.get_addr().tell(n);
+        resizable.get_addr().tell(n);
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let metrics = sys.render_metrics();
+    assert!(metrics.contains("aector_messages_handled_total{actor=\"workers\",message_type=\"u32\"} 6\n"), "{}", metrics);
+    assert!(metrics.contains("aector_messages_handled_total{actor=\"resizable\",message_type=\"u32\"} 6\n"), "{}", metrics);
+    assert!(!metrics.contains("anonymous"), "{}", metrics);
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "recording")]
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use aector::actor::{Actor, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{Behavior, BehaviorAction, BehaviorBuilder};
+use aector::recording::{Recorder, Replay};
+use aector::testing::TestProbe;
+
+#[derive(Serialize, Deserialize)]
+struct Add(u32);
+
+#[derive(Serialize, Deserialize)]
+struct Get;
+
+/// Message type which is not registered and thus not replayed.
+struct Ping;
+
+fn counter() -> Actor<u32> {
+    let behavior = BehaviorBuilder::new()
+        .on_tell::<Add>(|msg, state: &mut u32, _ctx| -> BehaviorAction<u32> {
+            *state += msg.0;
+            Behavior::keep()
+        })
+        .on_tell::<Ping>(|_msg, _state, _ctx| -> BehaviorAction<u32> {
+            Behavior::keep()
+        })
+        .on_ask::<Get>(|_msg, state, reply_to, _ctx| -> BehaviorAction<u32> {
+            reply_to.tell(*state);
+            Behavior::keep()
+        })
+        .build();
+    Actor::new(0, behavior, MailboxType::Unbounded)
+}
+
+async fn count(sys: &std::sync::Arc<ActorSystem>) -> u32 {
+    let mut probe = TestProbe::new();
+    sys.query("counter").unwrap().ask(Get, probe.addr());
+    probe.expect_msg::<u32>(Duration::from_secs(1)).await.unwrap()
+}
+
+#[tokio::test]
+async fn recorded_run_is_replayed_on_fresh_actors() {
+    let path = std::env::temp_dir().join(format!("aector-recording-{}.jsonl", std::process::id()));
+
+    // record a run
+    let sys = ActorSystem::new();
+    let recorder = Recorder::create(&path).unwrap()
+        .register::<Add>("Add")
+        .register::<Get>("Get");
+    sys.start_recording(recorder).unwrap();
+    let actor = sys.spawn(counter(), "counter".to_string()).unwrap();
+    let anonymous = sys.spawn_anonymous(counter());
+    for n in 1..=3 {
+        actor.get_addr().tell(Add(n));
+    }
+    actor.get_addr().tell(Ping);
+    anonymous.get_addr().tell(Add(10));
+    assert_eq!(count(&sys).await, 6);
+
+    // replay it on a fresh actor system, where the anonymous actor can not be spawned again
+    let sys = ActorSystem::new();
+    sys.spawn(counter(), "counter".to_string()).unwrap();
+    let replay = Replay::load(&path).unwrap()
+        .register::<Add>("Add")
+        .register::<Get>("Get");
+    assert_eq!(replay.len(), 6);
+    let report = replay.run(&sys, Duration::from_secs(1)).await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // the messages to the anonymous actor and of the unregistered type are skipped
+    assert_eq!(report.replayed, 4);
+    assert_eq!(report.skipped, 2);
+    assert_eq!(count(&sys).await, 6);
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use aector::Addr;
+use aector::actor::{Actor, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{Behavior, BehaviorAction, BehaviorBuilder};
+use aector::testing::TestProbe;
+
+enum Command {
+    Ping
+}
+
+#[test]
+fn typed_addr_requires_matching_handlers() {
+    let tell_only = BehaviorBuilder::<()>::new()
+        .on_tell::<Command>(|_msg, _state, _ctx| -> BehaviorAction<()> {
+            Behavior::keep()
+        })
+        .build();
+    let actor = Actor::new((), tell_only, MailboxType::Unbounded);
+    assert!(actor.get_typed_addr::<Command>().is_none());
+    assert!(actor.get_tell_addr::<Command>().is_some());
+    assert!(actor.get_ask_addr::<Command>().is_none());
+
+    let ask_only = BehaviorBuilder::<()>::new()
+        .on_ask::<Command>(|_msg, _state, _reply_to, _ctx| -> BehaviorAction<()> {
+            Behavior::keep()
+        })
+        .build();
+    let actor = Actor::new((), ask_only, MailboxType::Unbounded);
+    assert!(actor.get_typed_addr::<Command>().is_none());
+    assert!(actor.get_tell_addr::<Command>().is_none());
+    assert!(actor.get_ask_addr::<Command>().is_some());
+}
+
+#[tokio::test]
+async fn typed_addr_sends_with_tell_and_ask() {
+    let behavior = BehaviorBuilder::new()
+        .on_tell::<Command>(|_msg, probe: &mut Addr, _ctx| -> BehaviorAction<Addr> {
+            probe.tell("tell");
+            Behavior::keep()
+        })
+        .on_ask::<Command>(|_msg, _probe, reply_to, _ctx| -> BehaviorAction<Addr> {
+            reply_to.tell("ask");
+            Behavior::keep()
+        })
+        .build();
+    let mut probe = TestProbe::new();
+    let actor = Actor::new(probe.addr(), behavior, MailboxType::Unbounded);
+    let typed = actor.get_typed_addr::<Command>().unwrap();
+    let sys = ActorSystem::new();
+    sys.spawn_anonymous(actor);
+
+    typed.tell(Command::Ping);
+    assert_eq!(probe.expect_msg::<&'static str>(Duration::from_secs(1)).await.unwrap(), "tell");
+    typed.ask(Command::Ping, probe.addr());
+    assert_eq!(probe.expect_msg::<&'static str>(Duration::from_secs(1)).await.unwrap(), "ask");
+}
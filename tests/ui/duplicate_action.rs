@@ -0,0 +1,18 @@
+struct Counter {
+    count: i32
+}
+
+#[aector::actor]
+impl Counter {
+    #[on_start]
+    fn reset(&mut self, _ctx: &mut aector::actor::ActorContext) {
+        self.count = 0;
+    }
+
+    #[on_start]
+    fn log(&mut self, _ctx: &mut aector::actor::ActorContext) {
+        println!("started with count {}", self.count);
+    }
+}
+
+fn main() {}
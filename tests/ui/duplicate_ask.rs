@@ -0,0 +1,20 @@
+struct Counter {
+    count: i32
+}
+
+#[aector::actor]
+impl Counter {
+    #[ask]
+    fn get(&mut self, _msg: (), reply_to: aector::Addr, _ctx: &mut aector::actor::ActorContext) -> aector::behavior::BehaviorAction<Counter> {
+        reply_to.tell(self.count);
+        aector::behavior::Behavior::keep()
+    }
+
+    #[ask]
+    fn get_doubled(&mut self, _msg: (), reply_to: aector::Addr, _ctx: &mut aector::actor::ActorContext) -> aector::behavior::BehaviorAction<Counter> {
+        reply_to.tell(self.count * 2);
+        aector::behavior::Behavior::keep()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+struct Counter {
+    count: i32
+}
+
+#[aector::actor]
+impl Counter {
+    #[tell]
+    fn add(&mut self, msg: i32, _ctx: &mut aector::actor::ActorContext) -> aector::behavior::BehaviorAction<Counter> {
+        self.count += msg;
+        aector::behavior::Behavior::keep()
+    }
+
+    #[tell]
+    fn subtract(&mut self, msg: i32, _ctx: &mut aector::actor::ActorContext) -> aector::behavior::BehaviorAction<Counter> {
+        self.count -= msg;
+        aector::behavior::Behavior::keep()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,110 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tracing_subscriber::fmt::MakeWriter;
+
+use aector::actor::{Actor, Backup, ExitReason, MailboxType};
+use aector::actor_system::ActorSystem;
+use aector::behavior::{Behavior, BehaviorAction, BehaviorBuilder};
+use aector::supervision::{SuperVisionAction, SupervisionStrategy};
+use aector::watchdog::{Watchdog, WatchdogError};
+
+/// Collects the output of a tracing subscriber.
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Logs {
+    fn contains(&self, text: &str) -> bool {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).contains(text)
+    }
+}
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Logs {
+    type Writer = Logs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Behavior whose handler blocks for the given number of milliseconds.
+fn sleeping() -> Behavior<()> {
+    BehaviorBuilder::new()
+        .on_tell::<u64>(|millis, _state: &mut (), _ctx| -> BehaviorAction<()> {
+            std::thread::sleep(Duration::from_millis(millis));
+            Behavior::keep()
+        })
+        .build()
+}
+
+/// Records the watchdog errors of the supervised actor and lets it exit.
+struct RecordingStrategy(Arc<Mutex<Vec<Option<Duration>>>>);
+
+impl SupervisionStrategy<()> for RecordingStrategy {
+    fn apply(&mut self, _exit_reason: ExitReason, _backup: &Backup<()>, actor: &mut Actor<()>) -> SuperVisionAction {
+        let stalled = actor.watchdog_error().and_then(|err| match err {
+            WatchdogError::Stalled(duration) => Some(*duration),
+            _ => None
+        });
+        self.0.lock().unwrap().push(stalled);
+        SuperVisionAction::Exit
+    }
+}
+
+#[tokio::test]
+async fn watchdog_reports_slow_handlers() {
+    let logs = Logs::default();
+    let subscriber = tracing_subscriber::fmt().with_writer(logs.clone()).with_ansi(false).finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let sys = ActorSystem::new();
+    sys.start_watchdog(Watchdog::new().with_slow_handler_threshold(Duration::from_millis(10))).unwrap();
+    let actor = sys.spawn(Actor::new((), sleeping(), MailboxType::Unbounded), "sleeper".to_string()).unwrap();
+
+    actor.get_addr().tell(1u64);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!logs.contains("exceeds the threshold"));
+
+    actor.get_addr().tell(30u64);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(logs.contains("exceeds the threshold of 10ms"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn watchdog_supervises_stalled_actors() {
+    let sys = ActorSystem::new();
+    // a zero interval is clamped instead of panicking in the watchdog task
+    let watchdog = Watchdog::new()
+        .with_stall_timeout(Duration::from_millis(50))
+        .with_supervision(true)
+        .with_interval(Duration::ZERO);
+    sys.start_watchdog(watchdog).unwrap();
+
+    let exits = Arc::new(Mutex::new(Vec::new()));
+    let strategy = Box::new(RecordingStrategy(exits.clone()));
+    let actor = Actor::new((), sleeping(), MailboxType::Unbounded);
+    let actor = sys.spawn_with_supervision(actor, strategy, "stalled".to_string()).unwrap();
+
+    // the second message waits while the first one blocks the actor
+    actor.get_addr().tell(300u64);
+    actor.get_addr().tell(0u64);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let exits = exits.lock().unwrap();
+    assert_eq!(exits.len(), 1);
+    let stalled_for = exits[0].expect("actor did not exit with a watchdog error");
+    assert!(stalled_for > Duration::from_millis(50));
+    assert!(sys.list().is_empty());
+}